@@ -1,12 +1,17 @@
-use crate::client::{ApiError, Client, Method};
-use crate::error::Error;
-use crate::types::AgentConfiguration;
+use crate::client::{Client, Method, StatusCode};
+use crate::error::{ApiError, Error, RequestError};
+use crate::types::{
+  AgentConfiguration, Context, ContextOperation, ContextState, DecisionTree, DecisionTreeVersion,
+};
+use crate::utils::{context_operations_query, decision_tree_request};
+use futures::stream::{Stream, TryStreamExt};
+use serde::de::IgnoredAny;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize)]
-struct CreateAgentReqBody<'a> {
+pub(crate) struct CreateAgentReqBody<'a> {
   #[serde(rename = "id", skip_serializing_if = "Option::is_none")]
-  pub name: Option<String>,
+  pub name: Option<&'a str>,
   pub configuration: &'a AgentConfiguration,
 }
 
@@ -15,6 +20,10 @@ pub struct Agent {
   #[serde(rename = "id")]
   pub name: String,
   pub configuration: AgentConfiguration,
+  #[serde(rename = "firstTimestamp", skip_serializing_if = "Option::is_none")]
+  pub first_timestamp: Option<u64>,
+  #[serde(rename = "lastTimestamp", skip_serializing_if = "Option::is_none")]
+  pub last_timestamp: Option<u64>,
 }
 
 pub async fn create_agent<T: Into<String>>(
@@ -22,9 +31,10 @@ pub async fn create_agent<T: Into<String>>(
   name: T,
   configuration: &AgentConfiguration,
 ) -> Result<Agent, Error> {
+  let name = name.into();
   let req_body = CreateAgentReqBody {
-    name: Some(name.into()),
-    configuration: configuration,
+    name: Some(&name),
+    configuration,
   };
   client
     .request_project::<&str, CreateAgentReqBody, Agent>(Method::POST, "/agents", Some(&req_body))
@@ -37,18 +47,213 @@ pub async fn create_agent_with_generated_name(
 ) -> Result<Agent, Error> {
   let req_body = CreateAgentReqBody {
     name: None,
-    configuration: configuration,
+    configuration,
   };
   client
     .request_project::<&str, CreateAgentReqBody, Agent>(Method::POST, "/agents", Some(&req_body))
     .await
 }
 
+pub(crate) fn agent_path(name: &str) -> String {
+  format!("/agents/{}", name)
+}
+
+pub async fn get_agent<T: Into<String>>(client: &Client, name: T) -> Result<Agent, Error> {
+  client
+    .request_project::<String, (), Agent>(Method::GET, agent_path(&name.into()), None)
+    .await
+}
+
+// `GET /agents` wraps the names of a page in an `agentsList` object
+#[derive(Deserialize)]
+pub(crate) struct AgentsListResBody {
+  #[serde(rename = "agentsList")]
+  agents_list: Vec<String>,
+}
+
+impl IntoIterator for AgentsListResBody {
+  type Item = String;
+  type IntoIter = std::vec::IntoIter<String>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.agents_list.into_iter()
+  }
+}
+
+pub fn list_agents_stream(client: &Client) -> impl Stream<Item = Result<String, Error>> + '_ {
+  client.paginate_project::<_, AgentsListResBody>("/agents")
+}
+
+pub async fn list_agents(client: &Client) -> Result<Vec<String>, Error> {
+  list_agents_stream(client).try_collect().await
+}
+
+const AGENTS_BULK_CHUNK_SIZE: usize = 200;
+
+pub(crate) const AGENTS_BULK_PATH: &str = "/bulk/agents";
+
 #[derive(Deserialize)]
 #[serde(untagged)]
-enum DeleteAgentResBody {
-  Deleted(Agent),
-  NonExisting(ApiError),
+pub(crate) enum BulkAgentResBody {
+  Agent(Agent),
+  Error {
+    #[serde(rename = "id")]
+    name: String,
+    status: Option<u16>,
+    #[serde(flatten)]
+    api_error: ApiError,
+  },
+}
+
+fn bulk_agent_error(url: &str, method: Method, status: Option<u16>, api_error: ApiError) -> Error {
+  Error::from_request_error(
+    RequestError {
+      method,
+      url: url.to_string(),
+      status: status
+        .and_then(|status| StatusCode::from_u16(status).ok())
+        .unwrap_or(StatusCode::BAD_REQUEST),
+      api_error: Some(api_error),
+    },
+    None,
+  )
+}
+
+// `url` is the url of the bulk request, errors refer to it
+fn bulk_created_agent(agent_res_body: BulkAgentResBody, url: &str) -> Result<Agent, Error> {
+  match agent_res_body {
+    BulkAgentResBody::Agent(agent) => Ok(agent),
+    BulkAgentResBody::Error {
+      status, api_error, ..
+    } => Err(bulk_agent_error(url, Method::POST, status, api_error)),
+  }
+}
+
+fn bulk_deleted_agent(
+  agent_res_body: BulkAgentResBody,
+  url: &str,
+) -> Result<DeleteAgentResponse, Error> {
+  match agent_res_body {
+    BulkAgentResBody::Agent(agent) => Ok(DeleteAgentResponse::Deleted(agent.name)),
+    BulkAgentResBody::Error {
+      name,
+      status: Some(404),
+      ..
+    } => Ok(DeleteAgentResponse::NonExisting(name)),
+    BulkAgentResBody::Error {
+      status, api_error, ..
+    } => Err(bulk_agent_error(url, Method::DELETE, status, api_error)),
+  }
+}
+
+// Bulk requests are split in chunks of `AGENTS_BULK_CHUNK_SIZE` agents
+pub(crate) fn create_agents_bulk_req_bodies<'a, T: AsRef<str>>(
+  agents: &'a [(Option<T>, &'a AgentConfiguration)],
+) -> impl Iterator<Item = Vec<CreateAgentReqBody<'a>>> {
+  agents.chunks(AGENTS_BULK_CHUNK_SIZE).map(|agents_chunk| {
+    agents_chunk
+      .iter()
+      .map(|(name, configuration)| CreateAgentReqBody {
+        name: name.as_ref().map(AsRef::as_ref),
+        configuration,
+      })
+      .collect()
+  })
+}
+
+pub(crate) fn bulk_created_agents(
+  url: String,
+  res_body: Vec<BulkAgentResBody>,
+) -> impl Iterator<Item = Result<Agent, Error>> {
+  res_body
+    .into_iter()
+    .map(move |agent_res_body| bulk_created_agent(agent_res_body, &url))
+}
+
+pub async fn create_agents_bulk<T: AsRef<str>>(
+  client: &Client,
+  agents: &[(Option<T>, &AgentConfiguration)],
+) -> Result<Vec<Result<Agent, Error>>, Error> {
+  let mut responses = Vec::with_capacity(agents.len());
+  for req_body in create_agents_bulk_req_bodies(agents) {
+    let res_body = client
+      .request_project::<&str, Vec<CreateAgentReqBody>, Vec<BulkAgentResBody>>(
+        Method::POST,
+        AGENTS_BULK_PATH,
+        Some(&req_body),
+      )
+      .await?;
+    responses.extend(bulk_created_agents(
+      client.project_url(AGENTS_BULK_PATH),
+      res_body,
+    ));
+  }
+  Ok(responses)
+}
+
+#[derive(Serialize)]
+pub(crate) struct DeleteAgentReqBody<'a> {
+  #[serde(rename = "id")]
+  pub name: &'a str,
+}
+
+pub(crate) fn delete_agents_bulk_req_bodies<T: AsRef<str>>(
+  names: &[T],
+) -> impl Iterator<Item = Vec<DeleteAgentReqBody<'_>>> {
+  names.chunks(AGENTS_BULK_CHUNK_SIZE).map(|names_chunk| {
+    names_chunk
+      .iter()
+      .map(|name| DeleteAgentReqBody {
+        name: name.as_ref(),
+      })
+      .collect()
+  })
+}
+
+pub(crate) fn bulk_deleted_agents(
+  url: String,
+  res_body: Vec<BulkAgentResBody>,
+) -> impl Iterator<Item = Result<DeleteAgentResponse, Error>> {
+  res_body
+    .into_iter()
+    .map(move |agent_res_body| bulk_deleted_agent(agent_res_body, &url))
+}
+
+pub async fn delete_agents_bulk<T: AsRef<str>>(
+  client: &Client,
+  names: &[T],
+) -> Result<Vec<Result<DeleteAgentResponse, Error>>, Error> {
+  let mut responses = Vec::with_capacity(names.len());
+  for req_body in delete_agents_bulk_req_bodies(names) {
+    let res_body = client
+      .request_project::<&str, Vec<DeleteAgentReqBody>, Vec<BulkAgentResBody>>(
+        Method::DELETE,
+        AGENTS_BULK_PATH,
+        Some(&req_body),
+      )
+      .await?;
+    responses.extend(bulk_deleted_agents(
+      client.project_url(AGENTS_BULK_PATH),
+      res_body,
+    ));
+  }
+  Ok(responses)
+}
+
+// The platform answers with the deleted agent, or with an error message when there was none. Only
+// the shape of the body matters, an agent whose configuration can't be parsed was still deleted.
+#[derive(Deserialize)]
+pub(crate) struct DeleteAgentResBody {
+  configuration: Option<IgnoredAny>,
+}
+
+impl DeleteAgentResBody {
+  pub(crate) fn into_response(self, name: String) -> DeleteAgentResponse {
+    match self.configuration {
+      Some(_) => DeleteAgentResponse::Deleted(name),
+      None => DeleteAgentResponse::NonExisting(name),
+    }
+  }
 }
 
 #[derive(Debug, PartialEq)]
@@ -61,16 +266,200 @@ pub async fn delete_agent<T: Into<String>>(
   client: &Client,
   name: T,
 ) -> Result<DeleteAgentResponse, Error> {
-  let name_str = name.into();
-  match client
-    .request_project::<String, (), DeleteAgentResBody>(
-      Method::DELETE,
-      format!("/agents/{}", name_str),
+  let name = name.into();
+  Ok(
+    client
+      .request_project::<String, (), DeleteAgentResBody>(Method::DELETE, agent_path(&name), None)
+      .await?
+      .into_response(name),
+  )
+}
+
+const CONTEXT_OPERATIONS_CHUNK_SIZE: usize = 200;
+
+pub(crate) fn agent_context_path(name: &str) -> String {
+  format!("/agents/{}/context", name)
+}
+
+// Yields the chunks to upload along with the count of operations uploaded before them
+pub(crate) fn context_operations_chunks(
+  operations: &[ContextOperation],
+) -> impl Iterator<Item = (usize, &[ContextOperation])> {
+  operations
+    .chunks(CONTEXT_OPERATIONS_CHUNK_SIZE)
+    .enumerate()
+    .map(|(index, operations_chunk)| (index * CONTEXT_OPERATIONS_CHUNK_SIZE, operations_chunk))
+}
+
+// Uploads operations already checked against the agent configuration
+pub(crate) async fn upload_agent_context_operations(
+  client: &Client,
+  name: &str,
+  operations: &[ContextOperation],
+) -> Result<(), Error> {
+  let path = agent_context_path(name);
+  for (uploaded_count, operations_chunk) in context_operations_chunks(operations) {
+    client
+      .request_project::<&str, [ContextOperation], IgnoredAny>(
+        Method::POST,
+        &path,
+        Some(operations_chunk),
+      )
+      .await
+      .map_err(|err| err.partially_uploaded(uploaded_count))?;
+  }
+  Ok(())
+}
+
+// All the operations are checked against the agent configuration before the first upload
+pub(crate) fn check_context_operations(
+  operations: &[ContextOperation],
+  configuration: &AgentConfiguration,
+) -> Result<(), Error> {
+  operations
+    .iter()
+    .try_for_each(|operation| operation.check(configuration))
+}
+
+pub async fn add_agent_context_operations<T: Into<String>>(
+  client: &Client,
+  name: T,
+  operations: &[ContextOperation],
+) -> Result<(), Error> {
+  let name = name.into();
+  let configuration = get_agent(client, name.as_str()).await?.configuration;
+  check_context_operations(operations, &configuration)?;
+  upload_agent_context_operations(client, &name, operations).await
+}
+
+pub(crate) fn agent_context_operations_path(
+  name: &str,
+  lower_bound: Option<u64>,
+  upper_bound: Option<u64>,
+) -> String {
+  format!(
+    "{}{}",
+    agent_context_path(name),
+    context_operations_query(lower_bound, upper_bound)
+  )
+}
+
+pub async fn get_agent_context_operations<T: Into<String>>(
+  client: &Client,
+  name: T,
+  lower_bound: Option<u64>,
+  upper_bound: Option<u64>,
+) -> Result<Vec<ContextOperation>, Error> {
+  client
+    .paginate_project::<_, Vec<ContextOperation>>(agent_context_operations_path(
+      &name.into(),
+      lower_bound,
+      upper_bound,
+    ))
+    .try_collect()
+    .await
+}
+
+pub(crate) fn agent_context_state_path(name: &str, timestamp: u64) -> String {
+  format!("{}/state?t={}", agent_context_path(name), timestamp)
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ContextStateResBody {
+  timestamp: u64,
+  context: Context,
+}
+
+impl ContextStateResBody {
+  // The configuration is needed to type the values of the state
+  pub(crate) fn into_context_state(
+    self,
+    configuration: &AgentConfiguration,
+  ) -> Result<ContextState, Error> {
+    ContextState::from_context(self.timestamp, &self.context, configuration)
+  }
+}
+
+pub async fn get_agent_context_state<T: Into<String>>(
+  client: &Client,
+  name: T,
+  timestamp: u64,
+) -> Result<ContextState, Error> {
+  let name = name.into();
+  let agent = get_agent(client, name.as_str()).await?;
+  client
+    .request_project::<_, (), ContextStateResBody>(
+      Method::GET,
+      agent_context_state_path(&name, timestamp),
       None,
     )
     .await?
-  {
-    DeleteAgentResBody::Deleted(agent) => Ok(DeleteAgentResponse::Deleted(agent.name)),
-    DeleteAgentResBody::NonExisting(_) => Ok(DeleteAgentResponse::NonExisting(name_str)),
+    .into_context_state(&agent.configuration)
+}
+
+pub(crate) fn agent_decision_tree_request(
+  name: &str,
+  timestamp: Option<u64>,
+  version: Option<DecisionTreeVersion>,
+) -> (String, Vec<(&'static str, String)>) {
+  let (query_str, headers) = decision_tree_request(timestamp, version);
+  (
+    format!("{}/decision/tree{}", agent_path(name), query_str),
+    headers,
+  )
+}
+
+pub async fn get_agent_decision_tree<T: Into<String>>(
+  client: &Client,
+  name: T,
+  timestamp: Option<u64>,
+  version: Option<DecisionTreeVersion>,
+) -> Result<DecisionTree, Error> {
+  let (path, headers) = agent_decision_tree_request(&name.into(), timestamp, version);
+  client
+    .request_url_polling(client.project_url(path), &headers)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn delete_agent_res_body() {
+    // The configuration isn't parsed, a property type unknown to this crate doesn't matter
+    let res_body: DeleteAgentResBody = serde_json::from_value(json!({
+      "id": "foo",
+      "configuration": {"context": {"x": {"type": "brand_new_type"}}, "output": ["x"]}
+    }))
+    .unwrap();
+    assert_eq!(
+      res_body.into_response("foo".to_string()),
+      DeleteAgentResponse::Deleted("foo".to_string())
+    );
+    let res_body: DeleteAgentResBody =
+      serde_json::from_value(json!({"message": "Agent 'foo' does not exist"})).unwrap();
+    assert_eq!(
+      res_body.into_response("foo".to_string()),
+      DeleteAgentResponse::NonExisting("foo".to_string())
+    );
+  }
+
+  #[test]
+  fn bulk_deleted_agent_errors() {
+    let url = "https://beta.craft.ai/api/v1/foo/bar/bulk/agents";
+    let res_body: BulkAgentResBody = serde_json::from_value(json!({
+      "id": "foo", "status": 404, "message": "Agent 'foo' does not exist"
+    }))
+    .unwrap();
+    assert_eq!(
+      bulk_deleted_agent(res_body, url).unwrap(),
+      DeleteAgentResponse::NonExisting("foo".to_string())
+    );
+    // Only an explicit 404 means the agent didn't exist
+    let res_body: BulkAgentResBody =
+      serde_json::from_value(json!({"id": "foo", "message": "Unable to delete 'foo'"})).unwrap();
+    assert!(bulk_deleted_agent(res_body, url).is_err());
   }
 }
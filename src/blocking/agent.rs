@@ -0,0 +1,180 @@
+use super::Client;
+use crate::agent::{
+  agent_context_operations_path, agent_context_path, agent_context_state_path,
+  agent_decision_tree_request, agent_path, bulk_created_agents, bulk_deleted_agents,
+  check_context_operations, context_operations_chunks, create_agents_bulk_req_bodies,
+  delete_agents_bulk_req_bodies, Agent, AgentsListResBody, BulkAgentResBody, ContextStateResBody,
+  CreateAgentReqBody, DeleteAgentReqBody, DeleteAgentResBody, DeleteAgentResponse,
+  AGENTS_BULK_PATH,
+};
+use crate::client::Method;
+use crate::error::Error;
+use crate::types::{
+  AgentConfiguration, ContextOperation, ContextState, DecisionTree, DecisionTreeVersion,
+};
+use serde::de::IgnoredAny;
+
+pub fn create_agent<T: Into<String>>(
+  client: &Client,
+  name: T,
+  configuration: &AgentConfiguration,
+) -> Result<Agent, Error> {
+  let name = name.into();
+  let req_body = CreateAgentReqBody {
+    name: Some(&name),
+    configuration,
+  };
+  client.request_project::<&str, CreateAgentReqBody, Agent>(
+    Method::POST,
+    "/agents",
+    Some(&req_body),
+  )
+}
+
+pub fn create_agent_with_generated_name(
+  client: &Client,
+  configuration: &AgentConfiguration,
+) -> Result<Agent, Error> {
+  let req_body = CreateAgentReqBody {
+    name: None,
+    configuration,
+  };
+  client.request_project::<&str, CreateAgentReqBody, Agent>(
+    Method::POST,
+    "/agents",
+    Some(&req_body),
+  )
+}
+
+pub fn get_agent<T: Into<String>>(client: &Client, name: T) -> Result<Agent, Error> {
+  client.request_project::<String, (), Agent>(Method::GET, agent_path(&name.into()), None)
+}
+
+pub fn list_agents_iter(client: &Client) -> impl Iterator<Item = Result<String, Error>> + '_ {
+  client.paginate_project::<_, AgentsListResBody>("/agents")
+}
+
+pub fn list_agents(client: &Client) -> Result<Vec<String>, Error> {
+  list_agents_iter(client).collect()
+}
+
+pub fn create_agents_bulk<T: AsRef<str>>(
+  client: &Client,
+  agents: &[(Option<T>, &AgentConfiguration)],
+) -> Result<Vec<Result<Agent, Error>>, Error> {
+  let mut responses = Vec::with_capacity(agents.len());
+  for req_body in create_agents_bulk_req_bodies(agents) {
+    let res_body = client.request_project::<&str, Vec<CreateAgentReqBody>, Vec<BulkAgentResBody>>(
+      Method::POST,
+      AGENTS_BULK_PATH,
+      Some(&req_body),
+    )?;
+    responses.extend(bulk_created_agents(
+      client.project_url(AGENTS_BULK_PATH),
+      res_body,
+    ));
+  }
+  Ok(responses)
+}
+
+pub fn delete_agents_bulk<T: AsRef<str>>(
+  client: &Client,
+  names: &[T],
+) -> Result<Vec<Result<DeleteAgentResponse, Error>>, Error> {
+  let mut responses = Vec::with_capacity(names.len());
+  for req_body in delete_agents_bulk_req_bodies(names) {
+    let res_body = client.request_project::<&str, Vec<DeleteAgentReqBody>, Vec<BulkAgentResBody>>(
+      Method::DELETE,
+      AGENTS_BULK_PATH,
+      Some(&req_body),
+    )?;
+    responses.extend(bulk_deleted_agents(
+      client.project_url(AGENTS_BULK_PATH),
+      res_body,
+    ));
+  }
+  Ok(responses)
+}
+
+pub fn delete_agent<T: Into<String>>(
+  client: &Client,
+  name: T,
+) -> Result<DeleteAgentResponse, Error> {
+  let name = name.into();
+  Ok(
+    client
+      .request_project::<String, (), DeleteAgentResBody>(Method::DELETE, agent_path(&name), None)?
+      .into_response(name),
+  )
+}
+
+// Uploads operations already checked against the agent configuration
+pub(crate) fn upload_agent_context_operations(
+  client: &Client,
+  name: &str,
+  operations: &[ContextOperation],
+) -> Result<(), Error> {
+  let path = agent_context_path(name);
+  for (uploaded_count, operations_chunk) in context_operations_chunks(operations) {
+    client
+      .request_project::<&str, [ContextOperation], IgnoredAny>(
+        Method::POST,
+        &path,
+        Some(operations_chunk),
+      )
+      .map_err(|err| err.partially_uploaded(uploaded_count))?;
+  }
+  Ok(())
+}
+
+pub fn add_agent_context_operations<T: Into<String>>(
+  client: &Client,
+  name: T,
+  operations: &[ContextOperation],
+) -> Result<(), Error> {
+  let name = name.into();
+  let configuration = get_agent(client, name.as_str())?.configuration;
+  check_context_operations(operations, &configuration)?;
+  upload_agent_context_operations(client, &name, operations)
+}
+
+pub fn get_agent_context_operations<T: Into<String>>(
+  client: &Client,
+  name: T,
+  lower_bound: Option<u64>,
+  upper_bound: Option<u64>,
+) -> Result<Vec<ContextOperation>, Error> {
+  client
+    .paginate_project::<_, Vec<ContextOperation>>(agent_context_operations_path(
+      &name.into(),
+      lower_bound,
+      upper_bound,
+    ))
+    .collect()
+}
+
+pub fn get_agent_context_state<T: Into<String>>(
+  client: &Client,
+  name: T,
+  timestamp: u64,
+) -> Result<ContextState, Error> {
+  let name = name.into();
+  let agent = get_agent(client, name.as_str())?;
+  client
+    .request_project::<_, (), ContextStateResBody>(
+      Method::GET,
+      agent_context_state_path(&name, timestamp),
+      None,
+    )?
+    .into_context_state(&agent.configuration)
+}
+
+pub fn get_agent_decision_tree<T: Into<String>>(
+  client: &Client,
+  name: T,
+  timestamp: Option<u64>,
+  version: Option<DecisionTreeVersion>,
+) -> Result<DecisionTree, Error> {
+  let (path, headers) = agent_decision_tree_request(&name.into(), timestamp, version);
+  client.request_url_polling(client.project_url(path), &headers)
+}
@@ -0,0 +1,80 @@
+use super::Client;
+use crate::client::Method;
+use crate::error::Error;
+use crate::generator::{
+  generator_context_operations_path, generator_decision_tree_request, generator_path,
+  CreateGeneratorReqBody, DeleteGeneratorResBody, DeleteGeneratorResponse, Generator,
+  GeneratorsListResBody,
+};
+use crate::types::{
+  DecisionTree, DecisionTreeVersion, GeneratorConfiguration, GeneratorContextOperation,
+};
+
+pub fn create_generator<T: Into<String>>(
+  client: &Client,
+  name: T,
+  configuration: &GeneratorConfiguration,
+) -> Result<Generator, Error> {
+  let req_body = CreateGeneratorReqBody {
+    name: name.into(),
+    configuration,
+  };
+  client.request_project::<&str, CreateGeneratorReqBody, Generator>(
+    Method::POST,
+    "/generators",
+    Some(&req_body),
+  )
+}
+
+pub fn get_generator<T: Into<String>>(client: &Client, name: T) -> Result<Generator, Error> {
+  client.request_project::<String, (), Generator>(Method::GET, generator_path(&name.into()), None)
+}
+
+pub fn list_generators_iter(client: &Client) -> impl Iterator<Item = Result<String, Error>> + '_ {
+  client.paginate_project::<_, GeneratorsListResBody>("/generators")
+}
+
+pub fn list_generators(client: &Client) -> Result<Vec<String>, Error> {
+  list_generators_iter(client).collect()
+}
+
+pub fn delete_generator<T: Into<String>>(
+  client: &Client,
+  name: T,
+) -> Result<DeleteGeneratorResponse, Error> {
+  let name = name.into();
+  Ok(
+    client
+      .request_project::<String, (), DeleteGeneratorResBody>(
+        Method::DELETE,
+        generator_path(&name),
+        None,
+      )?
+      .into_response(name),
+  )
+}
+
+pub fn get_generator_context_operations<T: Into<String>>(
+  client: &Client,
+  name: T,
+  lower_bound: Option<u64>,
+  upper_bound: Option<u64>,
+) -> Result<Vec<GeneratorContextOperation>, Error> {
+  client
+    .paginate_project::<_, Vec<GeneratorContextOperation>>(generator_context_operations_path(
+      &name.into(),
+      lower_bound,
+      upper_bound,
+    ))
+    .collect()
+}
+
+pub fn get_generator_decision_tree<T: Into<String>>(
+  client: &Client,
+  name: T,
+  timestamp: Option<u64>,
+  version: Option<DecisionTreeVersion>,
+) -> Result<DecisionTree, Error> {
+  let (path, headers) = generator_decision_tree_request(&name.into(), timestamp, version);
+  client.request_url_polling(client.project_url(path), &headers)
+}
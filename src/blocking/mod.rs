@@ -0,0 +1,232 @@
+// Blocking counterpart of the async client, requests are made on reqwest's blocking client while
+// the steps of each call (throttling, retries, polling and pagination) are the ones of the async
+// client in `client.rs`. It must not be used from within an async runtime.
+
+mod agent;
+mod generator;
+mod operations_file;
+
+use crate::client::{network_error, project_url, Caller, ClientBuilder, ClientSettings, Method};
+use crate::error::Error;
+use crate::interceptor::Interceptors;
+use crate::retry::{PollingPolicy, RetryPolicy};
+use crate::throttle::Throttle;
+use crate::token::TokenPayload;
+use crate::transport::{HeaderMap, HttpRequest, HttpResponse};
+use crate::utils::ApiInfo;
+use futures::executor::{block_on, block_on_stream};
+use futures::future::{self, Ready};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+pub use agent::{
+  add_agent_context_operations, create_agent, create_agent_with_generated_name, create_agents_bulk,
+  delete_agent, delete_agents_bulk, get_agent, get_agent_context_operations,
+  get_agent_context_state, get_agent_decision_tree, list_agents, list_agents_iter,
+};
+pub use generator::{
+  create_generator, delete_generator, get_generator, get_generator_context_operations,
+  get_generator_decision_tree, list_generators, list_generators_iter,
+};
+pub use operations_file::{
+  add_agent_context_operations_from_file, add_agent_context_operations_from_reader,
+};
+
+pub struct Client {
+  pub token: String,
+  pub url: String,
+  pub owner: String,
+  pub project: String,
+  pub retry_policy: RetryPolicy,
+  pub polling_policy: PollingPolicy,
+  pub token_payload: TokenPayload,
+  default_headers: HeaderMap,
+  interceptors: Interceptors,
+  throttle: Throttle,
+  http_client: reqwest::blocking::Client,
+}
+
+fn send(
+  http_client: &reqwest::blocking::Client,
+  request: HttpRequest,
+) -> Result<HttpResponse, Error> {
+  let HttpRequest {
+    method,
+    url,
+    headers,
+    body,
+  } = request;
+  let mut request_builder = http_client.request(method, &url).headers(headers);
+  if let Some(body) = body {
+    request_builder = request_builder.body(body);
+  }
+  let mut response = request_builder
+    .send()
+    .map_err(|err| network_error(&url, err))?;
+  let mut body = Vec::new();
+  response
+    .copy_to(&mut body)
+    .map_err(|err| network_error(&url, err))?;
+  Ok(HttpResponse {
+    status: response.status(),
+    headers: response.headers().clone(),
+    body,
+  })
+}
+
+type SentRequest = Ready<Result<HttpResponse, Error>>;
+type Slept = Ready<()>;
+
+fn sleep(delay: Duration) -> Slept {
+  thread::sleep(delay);
+  future::ready(())
+}
+
+impl Client {
+  pub fn from_token(token: &str) -> Result<Client, Error> {
+    ClientBuilder::new(token).create_blocking_client()
+  }
+
+  pub(crate) fn from_settings(settings: ClientSettings) -> Result<Client, Error> {
+    let mut http_client_builder = reqwest::blocking::Client::builder();
+    if let Some(connect_timeout) = settings.connect_timeout {
+      http_client_builder = http_client_builder.connect_timeout(connect_timeout);
+    }
+    // Always set, `None` disables reqwest's 30s default for the blocking client like the async one
+    http_client_builder = http_client_builder.timeout(settings.timeout);
+    if let Some(proxy) = settings.proxy {
+      http_client_builder = http_client_builder.proxy(proxy);
+    }
+    let http_client = http_client_builder.build().map_err(|err| {
+      Error::InternalError(
+        "Unable to create the http client".to_string(),
+        Some(Box::new(err)),
+      )
+    })?;
+
+    Ok(Client {
+      token: settings.token,
+      url: settings.url,
+      owner: settings.owner,
+      project: settings.project,
+      retry_policy: settings.retry_policy,
+      polling_policy: settings.polling_policy,
+      token_payload: settings.token_payload,
+      default_headers: settings.default_headers,
+      interceptors: settings.interceptors,
+      throttle: settings.throttle,
+      http_client,
+    })
+  }
+
+  // Sending and sleeping block, the futures of the shared call steps are thus ready once polled
+  fn caller(
+    &self,
+  ) -> Caller<'_, impl Fn(HttpRequest) -> SentRequest + Copy + '_, fn(Duration) -> Slept> {
+    let http_client = &self.http_client;
+    Caller {
+      token: &self.token,
+      url: &self.url,
+      #[cfg(feature = "tracing")]
+      owner: &self.owner,
+      #[cfg(feature = "tracing")]
+      project: &self.project,
+      retry_policy: &self.retry_policy,
+      polling_policy: &self.polling_policy,
+      token_payload: &self.token_payload,
+      default_headers: &self.default_headers,
+      interceptors: &self.interceptors,
+      throttle: &self.throttle,
+      send: move |request| future::ready(send(http_client, request)),
+      sleep,
+    }
+  }
+
+  fn request_url<ReqBodyT: Serialize + ?Sized, ResBodyT: for<'de> Deserialize<'de>>(
+    &self,
+    method: Method,
+    url: &str,
+    request_body: Option<&ReqBodyT>,
+  ) -> Result<ResBodyT, Error> {
+    Ok(
+      block_on(
+        self
+          .caller()
+          .request_url_page(method, url, &[], request_body),
+      )?
+      .content,
+    )
+  }
+
+  pub(crate) fn request_url_polling<ResBodyT: for<'de> Deserialize<'de>>(
+    &self,
+    url: String,
+    headers: &[(&'static str, String)],
+  ) -> Result<ResBodyT, Error> {
+    block_on(self.caller().request_url_polling(url, headers))
+  }
+
+  pub fn request_path<
+    PathT: Into<String>,
+    ReqBodyT: Serialize + ?Sized,
+    ResBodyT: for<'de> Deserialize<'de>,
+  >(
+    &self,
+    method: Method,
+    path: PathT,
+    request_body: Option<&ReqBodyT>,
+  ) -> Result<ResBodyT, Error> {
+    self.request_url(
+      method,
+      &format!("{}{}", self.url, path.into()),
+      request_body,
+    )
+  }
+
+  pub fn request_project<
+    PathT: Into<String>,
+    ReqBodyT: Serialize + ?Sized,
+    ResBodyT: for<'de> Deserialize<'de>,
+  >(
+    &self,
+    method: Method,
+    path: PathT,
+    request_body: Option<&ReqBodyT>,
+  ) -> Result<ResBodyT, Error> {
+    self.request_url(method, &self.project_url(path), request_body)
+  }
+
+  // Pages are fetched lazily, while iterating
+  pub(crate) fn paginate_project<
+    'a,
+    PathT: Into<String>,
+    PageT: for<'de> Deserialize<'de> + IntoIterator + 'a,
+  >(
+    &'a self,
+    path: PathT,
+  ) -> impl Iterator<Item = Result<PageT::Item, Error>> + 'a {
+    block_on_stream(Box::pin(
+      self.caller().paginate::<PageT>(self.project_url(path)),
+    ))
+  }
+
+  pub(crate) fn project_url<PathT: Into<String>>(&self, path: PathT) -> String {
+    project_url(&self.url, &self.owner, &self.project, &path.into())
+  }
+}
+
+impl fmt::Display for Client {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "project '{}/{}' @ '{}'",
+      self.owner, self.project, self.url
+    )
+  }
+}
+
+pub fn ping(client: &Client) -> Result<ApiInfo, Error> {
+  client.request_path::<&str, (), ApiInfo>(Method::GET, "/api/v1", None)
+}
@@ -0,0 +1,61 @@
+use super::agent::{get_agent, upload_agent_context_operations};
+use super::Client;
+use crate::error::Error;
+use crate::operations_file::{
+  check_operations_file, open_operations_file, OperationsBatches, OperationsFileFormat,
+  UploadProgress,
+};
+use crate::types::AgentConfiguration;
+use std::io::Read;
+use std::path::Path;
+
+fn upload_operations<R: Read + Send, ProgressT: FnMut(usize)>(
+  client: &Client,
+  name: &str,
+  configuration: &AgentConfiguration,
+  reader: R,
+  format: OperationsFileFormat,
+  on_progress: ProgressT,
+) -> Result<usize, Error> {
+  let mut progress = UploadProgress::new(on_progress);
+  for batch in OperationsBatches::new(reader, format, configuration)? {
+    let batch = batch.map_err(|err| progress.failed(err))?;
+    upload_agent_context_operations(client, name, &batch).map_err(|err| progress.failed(err))?;
+    progress.uploaded(&batch);
+  }
+  Ok(progress.uploaded_count())
+}
+
+// Like the async version, only files are checked as a whole before the first upload
+pub fn add_agent_context_operations_from_reader<
+  T: Into<String>,
+  R: Read + Send,
+  ProgressT: FnMut(usize),
+>(
+  client: &Client,
+  name: T,
+  reader: R,
+  format: OperationsFileFormat,
+  on_progress: ProgressT,
+) -> Result<usize, Error> {
+  let name = name.into();
+  let configuration = get_agent(client, name.as_str())?.configuration;
+  upload_operations(client, &name, &configuration, reader, format, on_progress)
+}
+
+pub fn add_agent_context_operations_from_file<
+  T: Into<String>,
+  P: AsRef<Path>,
+  ProgressT: FnMut(usize),
+>(
+  client: &Client,
+  name: T,
+  path: P,
+  on_progress: ProgressT,
+) -> Result<usize, Error> {
+  let name = name.into();
+  let configuration = get_agent(client, name.as_str())?.configuration;
+  check_operations_file(path.as_ref(), &configuration)?;
+  let (file, format) = open_operations_file(path.as_ref())?;
+  upload_operations(client, &name, &configuration, file, format, on_progress)
+}
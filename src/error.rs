@@ -1,22 +1,145 @@
+use http::{Method, StatusCode};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::error;
 use std::fmt;
+use std::time::Duration;
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ApiError {
+  pub message: String,
+  #[serde(flatten)]
+  pub details: BTreeMap<String, Value>,
+}
+
+#[derive(Debug)]
+pub struct RequestError {
+  pub method: Method,
+  pub url: String,
+  pub status: StatusCode,
+  // `None` when the platform answered with an unparseable body (e.g. a gateway error page)
+  pub api_error: Option<ApiError>,
+}
 
 #[derive(Debug)]
 pub enum Error {
   InvalidArgument(String),
-  BadToken(String, Option<Box<dyn error::Error + 'static>>),
-  NetworkError(String, Box<dyn error::Error + 'static>),
-  InternalError(String, Option<Box<dyn error::Error + 'static>>),
+  BadRequest(RequestError),
+  Unauthorized(RequestError),
+  Forbidden(RequestError),
+  NotFound(RequestError),
+  Conflict(RequestError),
+  RateLimited {
+    request: RequestError,
+    retry_after: Option<Duration>,
+  },
+  ServerError(RequestError),
+  BadToken(
+    String,
+    Option<Box<dyn error::Error + Send + Sync + 'static>>,
+  ),
+  NetworkError(String, Box<dyn error::Error + Send + Sync + 'static>),
+  InternalError(
+    String,
+    Option<Box<dyn error::Error + Send + Sync + 'static>>,
+  ),
+  Timeout(String),
+  // Uploads made of several requests (e.g. from a file) can fail after some of them succeeded
+  PartiallyUploaded {
+    uploaded_count: usize,
+    error: Box<Error>,
+  },
+}
+
+impl Error {
+  pub(crate) fn from_request_error(request: RequestError, retry_after: Option<Duration>) -> Error {
+    match request.status {
+      StatusCode::UNAUTHORIZED => Error::Unauthorized(request),
+      StatusCode::FORBIDDEN => Error::Forbidden(request),
+      StatusCode::NOT_FOUND => Error::NotFound(request),
+      StatusCode::CONFLICT => Error::Conflict(request),
+      StatusCode::TOO_MANY_REQUESTS => Error::RateLimited {
+        request,
+        retry_after,
+      },
+      status if status.is_server_error() => Error::ServerError(request),
+      _ => Error::BadRequest(request),
+    }
+  }
+
+  pub fn request_error(&self) -> Option<&RequestError> {
+    match self {
+      Error::BadRequest(request)
+      | Error::Unauthorized(request)
+      | Error::Forbidden(request)
+      | Error::NotFound(request)
+      | Error::Conflict(request)
+      | Error::RateLimited { request, .. }
+      | Error::ServerError(request) => Some(request),
+      Error::PartiallyUploaded { error, .. } => error.request_error(),
+      _ => None,
+    }
+  }
+
+  pub fn status(&self) -> Option<StatusCode> {
+    self.request_error().map(|request| request.status)
+  }
+
+  // Accounts for the operations uploaded before the error, they add up with the ones it may report
+  pub(crate) fn partially_uploaded(self, uploaded_count: usize) -> Error {
+    match self {
+      Error::PartiallyUploaded {
+        uploaded_count: error_uploaded_count,
+        error,
+      } => error.partially_uploaded(uploaded_count + error_uploaded_count),
+      error if uploaded_count == 0 => error,
+      error => Error::PartiallyUploaded {
+        uploaded_count,
+        error: Box::new(error),
+      },
+    }
+  }
 }
 
 impl fmt::Display for Error {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
       Error::InvalidArgument(s) => write!(f, "Invalid argument - {}", s),
+      Error::ServerError(request) => write!(
+        f,
+        "Internal error - Unexpected error ({} '{}' -> {})",
+        request.method, request.url, request.status
+      ),
+      Error::BadRequest(request)
+      | Error::Unauthorized(request)
+      | Error::Forbidden(request)
+      | Error::NotFound(request)
+      | Error::Conflict(request)
+      | Error::RateLimited { request, .. } => write!(
+        f,
+        "Invalid argument - {} ({} '{}' -> {})",
+        request
+          .api_error
+          .as_ref()
+          .map_or("Unknown error", |api_error| api_error.message.as_str()),
+        request.method,
+        request.url,
+        request.status
+      ),
       Error::BadToken(s, _) => write!(f, "Bad token - {}", s),
-      Error::NetworkError(s, e) => write!(f, "Network error - {} ({})", s, e.to_string()),
-      Error::InternalError(s, Some(e)) => write!(f, "Internal error - {} ({})", s, e.to_string()),
+      Error::NetworkError(s, e) => write!(f, "Network error - {} ({})", s, e),
+      Error::InternalError(s, Some(e)) => write!(f, "Internal error - {} ({})", s, e),
       Error::InternalError(s, None) => write!(f, "Internal error - {}", s),
+      Error::Timeout(s) => write!(f, "Timeout - {}", s),
+      Error::PartiallyUploaded {
+        uploaded_count,
+        error,
+      } => write!(
+        f,
+        "{} ({} context operations were uploaded before failing)",
+        error, uploaded_count
+      ),
     }
   }
 }
@@ -27,7 +150,103 @@ impl error::Error for Error {
       Error::BadToken(_, Some(e)) => Some(e.as_ref()),
       Error::NetworkError(_, e) => Some(e.as_ref()),
       Error::InternalError(_, Some(e)) => Some(e.as_ref()),
+      Error::PartiallyUploaded { error, .. } => Some(error.as_ref()),
       _ => None,
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn request_error(status: StatusCode) -> RequestError {
+    RequestError {
+      method: Method::GET,
+      url: "https://craft.ai/api/v1/owner/project/agents/foo".to_string(),
+      status,
+      api_error: Some(ApiError {
+        message: "Something went wrong".to_string(),
+        details: BTreeMap::new(),
+      }),
+    }
+  }
+
+  #[test]
+  fn from_request_error() {
+    assert!(matches!(
+      Error::from_request_error(request_error(StatusCode::NOT_FOUND), None),
+      Error::NotFound(_)
+    ));
+    assert!(matches!(
+      Error::from_request_error(request_error(StatusCode::UNAUTHORIZED), None),
+      Error::Unauthorized(_)
+    ));
+    assert!(matches!(
+      Error::from_request_error(request_error(StatusCode::FORBIDDEN), None),
+      Error::Forbidden(_)
+    ));
+    assert!(matches!(
+      Error::from_request_error(request_error(StatusCode::CONFLICT), None),
+      Error::Conflict(_)
+    ));
+    assert!(matches!(
+      Error::from_request_error(request_error(StatusCode::UNPROCESSABLE_ENTITY), None),
+      Error::BadRequest(_)
+    ));
+    assert!(matches!(
+      Error::from_request_error(request_error(StatusCode::BAD_GATEWAY), None),
+      Error::ServerError(_)
+    ));
+    match Error::from_request_error(
+      request_error(StatusCode::TOO_MANY_REQUESTS),
+      Some(Duration::from_secs(3)),
+    ) {
+      Error::RateLimited { retry_after, .. } => {
+        assert_eq!(retry_after, Some(Duration::from_secs(3)))
+      }
+      error => panic!("Unexpected error {:?}", error),
+    }
+  }
+
+  #[test]
+  fn display_request_errors() {
+    let error = Error::from_request_error(request_error(StatusCode::NOT_FOUND), None);
+    assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Something went wrong (GET 'https://craft.ai/api/v1/owner/project/agents/foo' -> 404 Not Found)"
+    );
+    assert_eq!(
+      Error::from_request_error(request_error(StatusCode::INTERNAL_SERVER_ERROR), None).to_string(),
+      "Internal error - Unexpected error (GET 'https://craft.ai/api/v1/owner/project/agents/foo' -> 500 Internal Server Error)"
+    );
+  }
+
+  #[test]
+  fn api_error_from_json_string() {
+    let api_error = serde_json::from_str::<ApiError>(
+      "{\"message\":\"Agent 'foo' already exists\",\"code\":\"AgentAlreadyExists\"}",
+    )
+    .unwrap();
+    assert_eq!(api_error.message, "Agent 'foo' already exists");
+    assert_eq!(api_error.details["code"], "AgentAlreadyExists");
+  }
+
+  #[test]
+  fn partially_uploaded() {
+    let error = Error::InvalidArgument("Invalid operation".to_string());
+    assert!(matches!(
+      error.partially_uploaded(0),
+      Error::InvalidArgument(_)
+    ));
+    let error = Error::from_request_error(request_error(StatusCode::BAD_GATEWAY), None)
+      .partially_uploaded(200)
+      .partially_uploaded(10_000);
+    assert_eq!(error.status(), Some(StatusCode::BAD_GATEWAY));
+    assert_eq!(
+      error.to_string(),
+      "Internal error - Unexpected error (GET 'https://craft.ai/api/v1/owner/project/agents/foo' -> 502 Bad Gateway) (10200 context operations were uploaded before failing)"
+    );
+  }
+}
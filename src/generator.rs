@@ -0,0 +1,169 @@
+use crate::client::{Client, Method};
+use crate::error::Error;
+use crate::types::{
+  DecisionTree, DecisionTreeVersion, GeneratorConfiguration, GeneratorContextOperation,
+};
+use crate::utils::{context_operations_query, decision_tree_request};
+use futures::stream::{Stream, TryStreamExt};
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+pub(crate) struct CreateGeneratorReqBody<'a> {
+  #[serde(rename = "id")]
+  pub name: String,
+  pub configuration: &'a GeneratorConfiguration,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Generator {
+  #[serde(rename = "id")]
+  pub name: String,
+  pub configuration: GeneratorConfiguration,
+  #[serde(rename = "firstTimestamp", skip_serializing_if = "Option::is_none")]
+  pub first_timestamp: Option<u64>,
+  #[serde(rename = "lastTimestamp", skip_serializing_if = "Option::is_none")]
+  pub last_timestamp: Option<u64>,
+}
+
+pub async fn create_generator<T: Into<String>>(
+  client: &Client,
+  name: T,
+  configuration: &GeneratorConfiguration,
+) -> Result<Generator, Error> {
+  let req_body = CreateGeneratorReqBody {
+    name: name.into(),
+    configuration,
+  };
+  client
+    .request_project::<&str, CreateGeneratorReqBody, Generator>(
+      Method::POST,
+      "/generators",
+      Some(&req_body),
+    )
+    .await
+}
+
+pub(crate) fn generator_path(name: &str) -> String {
+  format!("/generators/{}", name)
+}
+
+pub async fn get_generator<T: Into<String>>(client: &Client, name: T) -> Result<Generator, Error> {
+  client
+    .request_project::<String, (), Generator>(Method::GET, generator_path(&name.into()), None)
+    .await
+}
+
+// `GET /generators` wraps the names of a page in a `generatorsList` object
+#[derive(Deserialize)]
+pub(crate) struct GeneratorsListResBody {
+  #[serde(rename = "generatorsList")]
+  generators_list: Vec<String>,
+}
+
+impl IntoIterator for GeneratorsListResBody {
+  type Item = String;
+  type IntoIter = std::vec::IntoIter<String>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.generators_list.into_iter()
+  }
+}
+
+pub fn list_generators_stream(client: &Client) -> impl Stream<Item = Result<String, Error>> + '_ {
+  client.paginate_project::<_, GeneratorsListResBody>("/generators")
+}
+
+pub async fn list_generators(client: &Client) -> Result<Vec<String>, Error> {
+  list_generators_stream(client).try_collect().await
+}
+
+// Like for agents, the deleted generator is recognized by its configuration
+#[derive(Deserialize)]
+pub(crate) struct DeleteGeneratorResBody {
+  configuration: Option<IgnoredAny>,
+}
+
+impl DeleteGeneratorResBody {
+  pub(crate) fn into_response(self, name: String) -> DeleteGeneratorResponse {
+    match self.configuration {
+      Some(_) => DeleteGeneratorResponse::Deleted(name),
+      None => DeleteGeneratorResponse::NonExisting(name),
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DeleteGeneratorResponse {
+  Deleted(String),
+  NonExisting(String),
+}
+
+pub async fn delete_generator<T: Into<String>>(
+  client: &Client,
+  name: T,
+) -> Result<DeleteGeneratorResponse, Error> {
+  let name = name.into();
+  Ok(
+    client
+      .request_project::<String, (), DeleteGeneratorResBody>(
+        Method::DELETE,
+        generator_path(&name),
+        None,
+      )
+      .await?
+      .into_response(name),
+  )
+}
+
+pub(crate) fn generator_context_operations_path(
+  name: &str,
+  lower_bound: Option<u64>,
+  upper_bound: Option<u64>,
+) -> String {
+  format!(
+    "{}/context{}",
+    generator_path(name),
+    context_operations_query(lower_bound, upper_bound)
+  )
+}
+
+pub async fn get_generator_context_operations<T: Into<String>>(
+  client: &Client,
+  name: T,
+  lower_bound: Option<u64>,
+  upper_bound: Option<u64>,
+) -> Result<Vec<GeneratorContextOperation>, Error> {
+  client
+    .paginate_project::<_, Vec<GeneratorContextOperation>>(generator_context_operations_path(
+      &name.into(),
+      lower_bound,
+      upper_bound,
+    ))
+    .try_collect()
+    .await
+}
+
+pub(crate) fn generator_decision_tree_request(
+  name: &str,
+  timestamp: Option<u64>,
+  version: Option<DecisionTreeVersion>,
+) -> (String, Vec<(&'static str, String)>) {
+  let (query_str, headers) = decision_tree_request(timestamp, version);
+  (
+    format!("{}/decision/tree{}", generator_path(name), query_str),
+    headers,
+  )
+}
+
+pub async fn get_generator_decision_tree<T: Into<String>>(
+  client: &Client,
+  name: T,
+  timestamp: Option<u64>,
+  version: Option<DecisionTreeVersion>,
+) -> Result<DecisionTree, Error> {
+  let (path, headers) = generator_decision_tree_request(&name.into(), timestamp, version);
+  client
+    .request_url_polling(client.project_url(path), &headers)
+    .await
+}
@@ -0,0 +1,64 @@
+use crate::error::Error;
+use crate::transport::{HttpRequest, HttpResponse};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Hooks called around each attempt of a request. `on_request` can alter the request before it is
+// sent (e.g. to add headers) or abort it by returning an error, `on_response` observes the outcome.
+pub trait Interceptor: Send + Sync {
+  fn on_request(&self, _request: &mut HttpRequest) -> Result<(), Error> {
+    Ok(())
+  }
+
+  fn on_response(
+    &self,
+    _request: &HttpRequest,
+    _response: Result<&HttpResponse, &Error>,
+    _elapsed: Duration,
+  ) {
+  }
+}
+
+// Closures are interceptors that only alter requests
+impl<F: Fn(&mut HttpRequest) -> Result<(), Error> + Send + Sync> Interceptor for F {
+  fn on_request(&self, request: &mut HttpRequest) -> Result<(), Error> {
+    self(request)
+  }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Interceptors(Vec<Arc<dyn Interceptor>>);
+
+impl fmt::Debug for Interceptors {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} interceptor(s)", self.0.len())
+  }
+}
+
+impl Interceptors {
+  pub fn push<I: Interceptor + 'static>(&mut self, interceptor: I) {
+    self.0.push(Arc::new(interceptor));
+  }
+
+  // Request hooks are called in the order the interceptors were added
+  pub fn intercept_request(&self, request: &HttpRequest) -> Result<HttpRequest, Error> {
+    let mut request = request.clone();
+    for interceptor in self.0.iter() {
+      interceptor.on_request(&mut request)?;
+    }
+    Ok(request)
+  }
+
+  // Response hooks are called in reverse order, the first added interceptor is the outermost one
+  pub fn observe_response(
+    &self,
+    request: &HttpRequest,
+    response: &Result<HttpResponse, Error>,
+    elapsed: Duration,
+  ) {
+    for interceptor in self.0.iter().rev() {
+      interceptor.on_response(request, response.as_ref(), elapsed);
+    }
+  }
+}
@@ -0,0 +1,372 @@
+use crate::error::Error;
+use crate::types::{
+  check_context_value, Context, DecisionNode, DecisionRule, DecisionTree, Distribution, LeafNode,
+  Operator, Time, TreeNode,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Decision {
+  pub predicted_value: Value,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub confidence: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub standard_deviation: Option<f64>,
+  pub decision_rules: Vec<DecisionRule>,
+}
+
+fn check_tree_version(tree: &DecisionTree) -> Result<(), Error> {
+  match tree.version.split('.').next() {
+    Some("1") | Some("2") => Ok(()),
+    _ => Err(Error::InvalidArgument(format!(
+      "Unsupported decision tree version '{}', only versions 1 and 2 are supported",
+      tree.version
+    ))),
+  }
+}
+
+fn check_context(tree: &DecisionTree, context: &Context) -> Result<(), Error> {
+  let configuration = &tree.configuration;
+  for property_name in context.keys() {
+    if !configuration.context.contains_key(property_name) {
+      return Err(Error::InvalidArgument(format!(
+        "Unable to take decision, unknown property '{}' in the given context",
+        property_name
+      )));
+    }
+  }
+  for (property_name, property) in configuration.context.iter() {
+    if configuration.output.contains(property_name) {
+      continue;
+    }
+    let is_valid = match context.get(property_name) {
+      None => property.is_optional.unwrap_or(false),
+      Some(value) => check_context_value(property, value),
+    };
+    if !is_valid {
+      return Err(Error::InvalidArgument(format!(
+        "Unable to take decision, invalid or missing value for property '{}' of type '{}'",
+        property_name, property.property_type
+      )));
+    }
+  }
+  Ok(())
+}
+
+fn values_equal(value: &Value, operand: &Value) -> bool {
+  match (value.as_f64(), operand.as_f64()) {
+    (Some(value), Some(operand)) => value == operand,
+    _ => value == operand,
+  }
+}
+
+fn check_rule(rule: &DecisionRule, value: &Value) -> bool {
+  match rule.operator {
+    Operator::Is => values_equal(value, &rule.operand),
+    Operator::In => match rule.operand.as_array() {
+      Some(operands) => operands.iter().any(|operand| values_equal(value, operand)),
+      None => false,
+    },
+    Operator::InInterval => {
+      let bounds = rule
+        .operand
+        .as_array()
+        .filter(|bounds| bounds.len() == 2)
+        .and_then(|bounds| Some((bounds[0].as_f64()?, bounds[1].as_f64()?)));
+      match (value.as_f64(), bounds) {
+        // Intervals on periodic properties (e.g. `[22, 6[` for a time of day) wrap around
+        (Some(value), Some((from, to))) if from > to => value >= from || value < to,
+        (Some(value), Some((from, to))) => value >= from && value < to,
+        _ => false,
+      }
+    }
+    Operator::LessThan => {
+      matches!((value.as_f64(), rule.operand.as_f64()), (Some(value), Some(operand)) if value < operand)
+    }
+    Operator::GreaterThanOrEqual => {
+      matches!((value.as_f64(), rule.operand.as_f64()), (Some(value), Some(operand)) if value >= operand)
+    }
+  }
+}
+
+fn decide_leaf(leaf_node: &LeafNode, decision_rules: Vec<DecisionRule>) -> Decision {
+  Decision {
+    predicted_value: leaf_node.prediction.value.clone(),
+    confidence: leaf_node.prediction.confidence,
+    standard_deviation: match leaf_node.prediction.distribution {
+      Some(Distribution::Continuous {
+        standard_deviation, ..
+      }) => Some(standard_deviation),
+      _ => None,
+    },
+    decision_rules,
+  }
+}
+
+fn decide_node(
+  node: &DecisionNode,
+  context: &Context,
+  mut decision_rules: Vec<DecisionRule>,
+) -> Result<Decision, Error> {
+  for child in node.children.iter() {
+    let rule = match child.decision_rule() {
+      Some(rule) => rule,
+      None => continue,
+    };
+    if !check_rule(rule, context.get(&rule.property).unwrap_or(&Value::Null)) {
+      continue;
+    }
+    decision_rules.push(rule.clone());
+    return match child {
+      TreeNode::Decision(decision_node) => decide_node(decision_node, context, decision_rules),
+      TreeNode::Leaf(leaf_node) => Ok(decide_leaf(leaf_node, decision_rules)),
+    };
+  }
+  let property = node
+    .children
+    .iter()
+    .find_map(TreeNode::decision_rule)
+    .map(|rule| rule.property.as_str())
+    .unwrap_or_default();
+  Err(Error::InvalidArgument(format!(
+    "Unable to take decision, value '{}' for property '{}' doesn't validate any decision rule",
+    context.get(property).unwrap_or(&Value::Null),
+    property
+  )))
+}
+
+pub fn decide(tree: &DecisionTree, context: &Context) -> Result<BTreeMap<String, Decision>, Error> {
+  check_tree_version(tree)?;
+  check_context(tree, context)?;
+  tree
+    .trees
+    .iter()
+    .map(|(output, root)| {
+      let decision = match root {
+        TreeNode::Decision(node) => decide_node(node, context, Vec::new())?,
+        TreeNode::Leaf(leaf_node) => decide_leaf(leaf_node, Vec::new()),
+      };
+      Ok((output.clone(), decision))
+    })
+    .collect()
+}
+
+// Fills the generated time properties of the context the same way the platform does before deciding
+pub fn decide_with_time(
+  tree: &DecisionTree,
+  context: &Context,
+  time: &Time,
+) -> Result<BTreeMap<String, Decision>, Error> {
+  let mut context = context.clone();
+  time.fill_context(&tree.configuration, &mut context)?;
+  decide(tree, &context)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn context(value: Value) -> Context {
+    serde_json::from_value(value).unwrap()
+  }
+
+  fn tree_v1() -> DecisionTree {
+    serde_json::from_value(json!({
+      "_version": "1.1.0",
+      "configuration": {
+        "context": {
+          "presence": {"type": "enum"},
+          "light": {"type": "continuous"},
+          "state": {"type": "enum"}
+        },
+        "output": ["state"]
+      },
+      "trees": {
+        "state": {
+          "children": [
+            {
+              "decision_rule": {"property": "presence", "operator": "is", "operand": "home"},
+              "children": [
+                {
+                  "decision_rule": {"property": "light", "operator": "<", "operand": 0.5},
+                  "predicted_value": "ON",
+                  "confidence": 0.9
+                },
+                {
+                  "decision_rule": {"property": "light", "operator": ">=", "operand": 0.5},
+                  "predicted_value": "OFF",
+                  "confidence": 0.8
+                }
+              ]
+            },
+            {
+              "decision_rule": {"property": "presence", "operator": "is", "operand": "away"},
+              "predicted_value": "OFF",
+              "confidence": 0.95
+            }
+          ]
+        }
+      }
+    }))
+    .unwrap()
+  }
+
+  fn tree_v2() -> DecisionTree {
+    serde_json::from_value(json!({
+      "_version": "2.0.0",
+      "configuration": {
+        "context": {
+          "time": {"type": "time_of_day"},
+          "room": {"type": "enum", "is_optional": true},
+          "power": {"type": "continuous"}
+        },
+        "output": ["power"]
+      },
+      "trees": {
+        "power": {
+          "children": [
+            {
+              "decision_rule": {"property": "time", "operator": "[in[", "operand": [22, 6]},
+              "prediction": {
+                "value": 1.5,
+                "confidence": 0.7,
+                "distribution": {"standard_deviation": 0.5, "mean": 1.5},
+                "nb_samples": 10
+              }
+            },
+            {
+              "decision_rule": {"property": "time", "operator": "[in[", "operand": [6, 22]},
+              "children": [
+                {
+                  "decision_rule": {"property": "room", "operator": "in", "operand": ["kitchen", "living"]},
+                  "prediction": {"value": 12, "confidence": 0.6, "distribution": {"standard_deviation": 2}}
+                },
+                {
+                  "decision_rule": {"property": "room", "operator": "in", "operand": ["bedroom"]},
+                  "prediction": {"value": 4, "confidence": 0.8, "distribution": {"standard_deviation": 1}}
+                },
+                {
+                  "decision_rule": {"property": "room", "operator": "is", "operand": null},
+                  "prediction": {"value": 8, "confidence": 0.4, "distribution": {"standard_deviation": 3}}
+                }
+              ]
+            }
+          ]
+        }
+      }
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn decide_v1() {
+    let decisions = decide(
+      &tree_v1(),
+      &context(json!({"presence": "home", "light": 0.7})),
+    )
+    .unwrap();
+    let decision = &decisions["state"];
+    assert_eq!(decision.predicted_value, json!("OFF"));
+    assert_eq!(decision.confidence, Some(0.8));
+    assert_eq!(decision.standard_deviation, None);
+    assert_eq!(decision.decision_rules.len(), 2);
+    assert_eq!(
+      decision.decision_rules[1].operator,
+      Operator::GreaterThanOrEqual
+    );
+
+    let decisions = decide(
+      &tree_v1(),
+      &context(json!({"presence": "away", "light": 0.7})),
+    )
+    .unwrap();
+    assert_eq!(decisions["state"].predicted_value, json!("OFF"));
+    assert_eq!(decisions["state"].decision_rules.len(), 1);
+  }
+
+  #[test]
+  fn decide_v2_periodic_wraparound() {
+    let decisions = decide(
+      &tree_v2(),
+      &context(json!({"time": 23.5, "room": "kitchen"})),
+    )
+    .unwrap();
+    assert_eq!(decisions["power"].predicted_value, json!(1.5));
+    assert_eq!(decisions["power"].standard_deviation, Some(0.5));
+    let decisions = decide(&tree_v2(), &context(json!({"time": 2, "room": "kitchen"}))).unwrap();
+    assert_eq!(decisions["power"].predicted_value, json!(1.5));
+  }
+
+  #[test]
+  fn decide_v2_in_and_null() {
+    let decisions = decide(&tree_v2(), &context(json!({"time": 12, "room": "living"}))).unwrap();
+    assert_eq!(decisions["power"].predicted_value, json!(12));
+    assert_eq!(decisions["power"].confidence, Some(0.6));
+    assert_eq!(decisions["power"].decision_rules.len(), 2);
+    let decisions = decide(&tree_v2(), &context(json!({"time": 12}))).unwrap();
+    assert_eq!(decisions["power"].predicted_value, json!(8));
+  }
+
+  #[cfg(feature = "tz")]
+  #[test]
+  fn decide_with_generated_time() {
+    let time = Time::new(1_464_600_000, "Europe/Paris").unwrap();
+    let decisions =
+      decide_with_time(&tree_v2(), &context(json!({"room": "bedroom"})), &time).unwrap();
+    assert_eq!(decisions["power"].predicted_value, json!(4));
+    let time = Time::new(1_464_600_000, "-10:00").unwrap();
+    let decisions =
+      decide_with_time(&tree_v2(), &context(json!({"room": "bedroom"})), &time).unwrap();
+    assert_eq!(decisions["power"].predicted_value, json!(1.5));
+  }
+
+  #[test]
+  fn decide_no_matching_rule() {
+    let error = decide(&tree_v2(), &context(json!({"time": 12, "room": "garage"}))).unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Unable to take decision, value '\"garage\"' for property 'room' doesn't validate any decision rule"
+    );
+  }
+
+  #[test]
+  fn decide_invalid_context() {
+    let error = decide(&tree_v1(), &context(json!({"presence": "home"}))).unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Unable to take decision, invalid or missing value for property 'light' of type 'continuous'"
+    );
+    let error = decide(
+      &tree_v1(),
+      &context(json!({"presence": "home", "light": "bright"})),
+    )
+    .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Unable to take decision, invalid or missing value for property 'light' of type 'continuous'"
+    );
+    let error = decide(
+      &tree_v1(),
+      &context(json!({"presence": "home", "light": 0.2, "foo": 1})),
+    )
+    .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Unable to take decision, unknown property 'foo' in the given context"
+    );
+  }
+
+  #[test]
+  fn decide_unsupported_version() {
+    let mut tree = tree_v1();
+    tree.version = "3.0.0".to_string();
+    let error = decide(&tree, &context(json!({"presence": "home", "light": 0.2}))).unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Unsupported decision tree version '3.0.0', only versions 1 and 2 are supported"
+    );
+  }
+}
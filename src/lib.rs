@@ -1,14 +1,50 @@
 mod agent;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 mod client;
 mod error;
+mod generator;
+mod interceptor;
+mod interpreter;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod operations_file;
+mod retry;
+mod throttle;
 mod token;
+#[cfg(feature = "tracing")]
+mod trace;
+mod transport;
 mod types;
 mod utils;
 
 pub use agent::{
-  create_agent, create_agent_with_generated_name, delete_agent, Agent, DeleteAgentResponse,
+  add_agent_context_operations, create_agent, create_agent_with_generated_name, create_agents_bulk,
+  delete_agent, delete_agents_bulk, get_agent, get_agent_context_operations,
+  get_agent_context_state, get_agent_decision_tree, list_agents, list_agents_stream, Agent,
+  DeleteAgentResponse,
+};
+pub use client::{Client, ClientBuilder, Method, StatusCode};
+pub use error::{ApiError, Error, RequestError};
+pub use generator::{
+  create_generator, delete_generator, get_generator, get_generator_context_operations,
+  get_generator_decision_tree, list_generators, list_generators_stream, DeleteGeneratorResponse,
+  Generator,
+};
+pub use interceptor::Interceptor;
+pub use interpreter::{decide, decide_with_time, Decision};
+pub use operations_file::{
+  add_agent_context_operations_from_file, add_agent_context_operations_from_reader,
+  OperationsFileFormat,
+};
+pub use retry::{PollingPolicy, RetryPolicy};
+pub use throttle::RateLimit;
+pub use token::TokenPayload;
+pub use transport::{HeaderMap, HttpRequest, HttpResponse, ReqwestTransport, Transport};
+pub use types::{
+  AgentConfiguration, ConfigurationBuilder, Context, ContextBuilder, ContextOperation,
+  ContextState, ContextValue, DecisionNode, DecisionRule, DecisionTree, DecisionTreeVersion,
+  Distribution, GeneratorConfiguration, GeneratorContextOperation, LeafNode, Operator, Prediction,
+  PropertyConfiguration, PropertyType, Time, TreeNode,
 };
-pub use client::Client;
-pub use error::Error;
-pub use types::{AgentConfiguration, ConfigurationBuilder, PropertyType};
 pub use utils::ping;
@@ -1,14 +1,22 @@
-use clap::{load_yaml, App};
-use serde::Deserialize;
+use clap::{load_yaml, App, ArgMatches};
+use futures::stream::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::env;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::BufReader;
 
 use dotenv::dotenv;
 
+mod output;
+
+use crate::output::OutputFormat;
+
 use craft_ai::{
-  create_agent, create_agent_with_generated_name, delete_agent, ping, AgentConfiguration, Client,
-  DeleteAgentResponse, Error,
+  add_agent_context_operations_from_file, create_agent, create_agent_with_generated_name,
+  delete_agent, get_agent, get_agent_context_operations, get_agent_context_state,
+  get_agent_decision_tree, list_agents_stream, ping, AgentConfiguration, Client,
+  DecisionTreeVersion, DeleteAgentResponse, Error,
 };
 
 fn deserialize_json_file<ContentT: for<'de> Deserialize<'de>, PathT: Into<String>>(
@@ -17,83 +25,198 @@ fn deserialize_json_file<ContentT: for<'de> Deserialize<'de>, PathT: Into<String
   let path_str = path.into();
   let file = File::open(&path_str)
     .map_err(|_err| Error::InvalidArgument(format!("Unable to open '{}'.", path_str)))?;
-  let mut buf_reader = BufReader::new(file);
-  let mut file_content = String::new();
-  buf_reader
-    .read_to_string(&mut file_content)
-    .map_err(|_err| Error::InvalidArgument(format!("Unable to read data from '{}'.", path_str)))?;
-  Ok(
-    serde_json::from_str::<ContentT>(&file_content).map_err(|_err| {
-      Error::InvalidArgument(format!("Unable to deserialize from '{}'.", path_str))
-    })?,
-  )
+  serde_json::from_reader::<_, ContentT>(BufReader::new(file))
+    .map_err(|_err| Error::InvalidArgument(format!("Unable to deserialize from '{}'.", path_str)))
 }
 
-#[tokio::main]
-async fn main() {
-  // Load dotenv
-  dotenv().ok();
-
-  let yaml = load_yaml!("cli.yml");
-  let app = App::from_yaml(yaml);
-  let matches = app.get_matches();
+fn to_document<ContentT: Serialize>(content: &ContentT) -> Result<Value, Error> {
+  serde_json::to_value(content).map_err(|err| {
+    Error::InternalError(
+      "Unable to serialize the result".to_string(),
+      Some(Box::new(err)),
+    )
+  })
+}
 
-  let token_from_env = env::var("CRAFT_TOKEN").unwrap_or("".to_string());
-  let token = matches.value_of("token").unwrap_or(&token_from_env);
-  let client = Client::from_token(token).unwrap();
-  println!("Interacting with {}.", client);
+fn parse_timestamp(matches: &ArgMatches, arg_name: &str) -> Result<Option<u64>, Error> {
+  matches
+    .value_of(arg_name)
+    .map(|timestamp| {
+      timestamp.parse::<u64>().map_err(|_err| {
+        Error::InvalidArgument(format!(
+          "Invalid timestamp '{}' for {}",
+          timestamp, arg_name
+        ))
+      })
+    })
+    .transpose()
+}
 
-  if let Some(_) = matches.subcommand_matches("ping") {
-    let response = ping(&client).await.unwrap();
-    println!(
-      "Remote craft ai uses version {} with the following activated features {:?}",
-      response.version, response.activated_features
-    );
-    std::process::exit(0);
+// Documented in the `after_help` section of `cli.yml`, keep both in sync
+fn exit_code(error: &Error) -> i32 {
+  match error {
+    Error::InvalidArgument(_) => 2,
+    Error::BadRequest(_) => 3,
+    Error::Unauthorized(_) => 4,
+    Error::Forbidden(_) => 5,
+    Error::NotFound(_) => 6,
+    Error::Conflict(_) => 7,
+    Error::RateLimited { .. } => 8,
+    Error::ServerError(_) => 9,
+    Error::BadToken(_, _) => 10,
+    Error::NetworkError(_, _) => 11,
+    Error::Timeout(_) => 12,
+    Error::InternalError(_, _) => 1,
+    // Scripts must know some operations were uploaded, the cause is only written on stderr
+    Error::PartiallyUploaded { .. } => 13,
   }
-  if let Some(agent_matches) = matches.subcommand_matches("agents") {
-    if let Some(create_agent_matches) = agent_matches.subcommand_matches("create") {
+}
+
+async fn run_agents(client: &Client, agent_matches: &ArgMatches<'_>) -> Result<Value, Error> {
+  match agent_matches.subcommand() {
+    ("create", Some(create_agent_matches)) => {
       let configuration_file_path = create_agent_matches
         .value_of("AGENT_CONFIGURATION_FILE")
-        .unwrap();
+        .unwrap_or_default();
       let configuration =
-        deserialize_json_file::<AgentConfiguration, &str>(configuration_file_path).unwrap();
+        deserialize_json_file::<AgentConfiguration, &str>(configuration_file_path)?;
       let created_agent = match create_agent_matches.value_of("name") {
-        Some(agent_name) => create_agent(&client, agent_name, &configuration)
-          .await
-          .unwrap(),
-        None => create_agent_with_generated_name(&client, &configuration)
-          .await
-          .unwrap(),
+        Some(agent_name) => create_agent(client, agent_name, &configuration).await?,
+        None => create_agent_with_generated_name(client, &configuration).await?,
       };
-      println!(
-        "Sucessfully created agent '{}/{}/{}'\n---\n{}",
-        client.owner,
-        client.project,
-        created_agent.name,
-        serde_json::to_string_pretty(&created_agent).unwrap()
+      eprintln!(
+        "Sucessfully created agent '{}/{}/{}'",
+        client.owner, client.project, created_agent.name
       );
-      std::process::exit(0);
+      to_document(&created_agent)
+    }
+    ("get", Some(get_agent_matches)) => {
+      let name = get_agent_matches.value_of("NAME").unwrap_or_default();
+      to_document(&get_agent(client, name).await?)
     }
-    if let Some(create_agent_matches) = agent_matches.subcommand_matches("delete") {
-      let name = create_agent_matches.value_of("NAME").unwrap();
-      match delete_agent(&client, name).await.unwrap() {
+    ("list", Some(_)) => {
+      let agent_names: Vec<String> = list_agents_stream(client).try_collect().await?;
+      to_document(&agent_names)
+    }
+    ("delete", Some(delete_agent_matches)) => {
+      let name = delete_agent_matches.value_of("NAME").unwrap_or_default();
+      match delete_agent(client, name).await? {
         DeleteAgentResponse::Deleted(deleted_agent_name) => {
-          println!(
+          eprintln!(
             "Sucessfully deleted agent '{}/{}/{}'",
             client.owner, client.project, deleted_agent_name
           );
+          Ok(json!({"name": deleted_agent_name, "deleted": true}))
         }
         DeleteAgentResponse::NonExisting(deleted_agent_name) => {
-          println!(
+          eprintln!(
             "No agent named '{}/{}/{}'",
             client.owner, client.project, deleted_agent_name
           );
+          Ok(json!({"name": deleted_agent_name, "deleted": false}))
         }
       }
-      std::process::exit(0);
     }
+    ("add-operations", Some(add_operations_matches)) => {
+      let name = add_operations_matches.value_of("NAME").unwrap_or_default();
+      let file_path = add_operations_matches.value_of("FILE").unwrap_or_default();
+      let uploaded_count =
+        add_agent_context_operations_from_file(client, name, file_path, |uploaded_count| {
+          eprintln!("{} context operations uploaded...", uploaded_count)
+        })
+        .await?;
+      eprintln!(
+        "Sucessfully added {} context operations to agent '{}/{}/{}'",
+        uploaded_count, client.owner, client.project, name
+      );
+      Ok(json!({"name": name, "added_operations": uploaded_count}))
+    }
+    ("operations", Some(operations_matches)) => {
+      let name = operations_matches.value_of("NAME").unwrap_or_default();
+      let operations = get_agent_context_operations(
+        client,
+        name,
+        parse_timestamp(operations_matches, "from")?,
+        parse_timestamp(operations_matches, "to")?,
+      )
+      .await?;
+      to_document(&operations)
+    }
+    ("state", Some(state_matches)) => {
+      let name = state_matches.value_of("NAME").unwrap_or_default();
+      let timestamp = parse_timestamp(state_matches, "TIMESTAMP")?.unwrap_or_default();
+      to_document(&get_agent_context_state(client, name, timestamp).await?)
+    }
+    ("tree", Some(tree_matches)) => {
+      let name = tree_matches.value_of("NAME").unwrap_or_default();
+      let version = match tree_matches.value_of("version") {
+        Some("1") => Some(DecisionTreeVersion::V1),
+        Some("2") => Some(DecisionTreeVersion::V2),
+        Some(version) => {
+          return Err(Error::InvalidArgument(format!(
+            "Unsupported decision tree version '{}'",
+            version
+          )))
+        }
+        None => None,
+      };
+      let decision_tree = get_agent_decision_tree(
+        client,
+        name,
+        parse_timestamp(tree_matches, "timestamp")?,
+        version,
+      )
+      .await?;
+      to_document(&decision_tree)
+    }
+    (subcommand, _) => Err(Error::InvalidArgument(format!(
+      "Unknown agents subcommand '{}'",
+      subcommand
+    ))),
+  }
+}
+
+async fn run(matches: &ArgMatches<'_>) -> Result<(), Error> {
+  let output_format = OutputFormat::from_name(matches.value_of("output").unwrap_or("json"))?;
+  let token_from_env = env::var("CRAFT_TOKEN").unwrap_or_default();
+  let token = matches.value_of("token").unwrap_or(&token_from_env);
+  let client = Client::from_token(token)?;
+  eprintln!("Interacting with {}.", client);
+
+  let document = match matches.subcommand() {
+    ("ping", Some(_)) => to_document(&ping(&client).await?)?,
+    ("agents", Some(agent_matches)) => run_agents(&client, agent_matches).await?,
+    (subcommand, _) => {
+      return Err(Error::InvalidArgument(format!(
+        "Unknown subcommand '{}'",
+        subcommand
+      )))
+    }
+  };
+  if let Some(rendered_document) = output_format.render(&document)? {
+    println!("{}", rendered_document.trim_end());
   }
+  Ok(())
+}
 
-  panic!("Unhandled CLI options");
+#[tokio::main]
+async fn main() {
+  // Load dotenv
+  dotenv().ok();
+
+  let yaml = load_yaml!("cli.yml");
+  let app = App::from_yaml(yaml);
+  let matches = app.get_matches_safe().unwrap_or_else(|error| {
+    // Help and version are displayed on stdout and aren't failures
+    if !error.use_stderr() {
+      error.exit()
+    }
+    eprintln!("{}", error.message);
+    std::process::exit(2)
+  });
+
+  if let Err(error) = run(&matches).await {
+    eprintln!("{}", error);
+    std::process::exit(exit_code(&error));
+  }
 }
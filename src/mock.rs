@@ -0,0 +1,827 @@
+use crate::client::{network_error, Client, ClientBuilder};
+use crate::error::Error;
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+use crate::types::{
+  AgentConfiguration, Context, ContextOperation, GeneratorConfiguration, GeneratorContextOperation,
+};
+use futures::future::{self, BoxFuture};
+use hyper::header::{HOST, LOCATION, RETRY_AFTER};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+pub const MOCK_OWNER: &str = "mock-owner";
+pub const MOCK_PROJECT: &str = "mock-project";
+pub const MOCK_TRANSPORT_URL: &str = "http://mock.craft.ai";
+
+const PAGE_SIZE: usize = 100;
+const CONTEXT_OPERATIONS_PAGE_SIZE: usize = 200;
+
+pub fn mint_token(platform: &str, owner: &str, project: &str) -> String {
+  let header = json!({"typ": "JWT", "alg": "HS256"});
+  let payload = json!({
+    "owner": owner,
+    "project": project,
+    "right": "write",
+    "platform": platform,
+    "iat": 0,
+    "iss": platform,
+  });
+  format!(
+    "{}.{}.mock-signature",
+    base64::encode_config(&header.to_string(), base64::URL_SAFE_NO_PAD),
+    base64::encode_config(&payload.to_string(), base64::URL_SAFE_NO_PAD)
+  )
+}
+
+struct MockAgent {
+  configuration: AgentConfiguration,
+  operations: Vec<ContextOperation>,
+}
+
+struct MockGenerator {
+  configuration: GeneratorConfiguration,
+}
+
+// Error answered to a request instead of handling it, to simulate an overloaded platform
+#[derive(Clone, Debug)]
+pub struct MockFailure {
+  pub status: StatusCode,
+  pub retry_after: Option<u64>,
+}
+
+impl MockFailure {
+  fn response(&self) -> Response<Body> {
+    let mut response = error_response(self.status, "Simulated failure".to_string());
+    if let Some(retry_after) = self.retry_after {
+      response
+        .headers_mut()
+        .insert(RETRY_AFTER, retry_after.to_string().parse().unwrap());
+    }
+    response
+  }
+}
+
+#[derive(Default)]
+struct MockState {
+  agents: BTreeMap<String, MockAgent>,
+  generators: BTreeMap<String, MockGenerator>,
+  generated_names_count: usize,
+  failures: VecDeque<MockFailure>,
+  requests_count: usize,
+  pending_computations: usize,
+  computations_location: Option<String>,
+}
+
+// Handle on the state of a mock platform, shared by `MockServer` and `MockTransport` to simulate
+// failures and slow computations
+#[derive(Clone, Default)]
+pub struct MockPlatform {
+  state: Arc<Mutex<MockState>>,
+}
+
+impl MockPlatform {
+  // The next `polls` decision tree requests are answered with `202 Accepted`, the location to poll
+  // is the requested one unless given.
+  pub fn delay_computations(&self, polls: usize, location: Option<&str>) {
+    let mut state = self.state.lock().unwrap();
+    state.pending_computations = polls;
+    state.computations_location = location.map(str::to_string);
+  }
+
+  // The next `count` requests are answered with the given failure
+  pub fn push_failures(&self, failure: MockFailure, count: usize) {
+    let mut state = self.state.lock().unwrap();
+    for _ in 0..count {
+      state.failures.push_back(failure.clone());
+    }
+  }
+
+  // Count of received requests, failed ones included
+  pub fn requests_count(&self) -> usize {
+    self.state.lock().unwrap().requests_count
+  }
+}
+
+pub struct MockServer {
+  url: String,
+  token: String,
+  platform: MockPlatform,
+  shutdown_sender: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+  // Must be called from within a tokio runtime, the server is spawned on it
+  pub fn start() -> Result<MockServer, Error> {
+    let server_builder =
+      Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0))).map_err(|err| {
+        Error::InternalError(
+          "Unable to bind the mock server".to_string(),
+          Some(Box::new(err)),
+        )
+      })?;
+    let platform = MockPlatform::default();
+    let service_state = platform.state.clone();
+    let make_service = make_service_fn(move |_| {
+      let state = service_state.clone();
+      async move {
+        Ok::<_, Infallible>(service_fn(move |request| {
+          handle_request(state.clone(), request)
+        }))
+      }
+    });
+    let server = server_builder.serve(make_service);
+    let url = format!("http://{}", server.local_addr());
+    let token = mint_token(&url, MOCK_OWNER, MOCK_PROJECT);
+    let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+    tokio::spawn(server.with_graceful_shutdown(async {
+      // Dropping the `MockServer` drops the sender, which must not stop the server
+      if shutdown_receiver.await.is_err() {
+        future::pending::<()>().await;
+      }
+    }));
+    Ok(MockServer {
+      url,
+      token,
+      platform,
+      shutdown_sender: Some(shutdown_sender),
+    })
+  }
+
+  pub fn url(&self) -> &str {
+    &self.url
+  }
+
+  pub fn token(&self) -> &str {
+    &self.token
+  }
+
+  pub fn client_builder(&self) -> ClientBuilder {
+    ClientBuilder::new(self.token.as_str())
+  }
+
+  pub fn client(&self) -> Result<Client, Error> {
+    self.client_builder().create_client()
+  }
+
+  pub fn platform(&self) -> &MockPlatform {
+    &self.platform
+  }
+
+  // Without an explicit shutdown the server runs as long as the runtime it was started on
+  pub fn shutdown(mut self) {
+    if let Some(shutdown_sender) = self.shutdown_sender.take() {
+      shutdown_sender.send(()).ok();
+    }
+  }
+}
+
+// In-memory mock platform, requests are handled without binding a port nor needing a runtime
+#[derive(Clone)]
+pub struct MockTransport {
+  platform: MockPlatform,
+}
+
+impl Default for MockTransport {
+  fn default() -> MockTransport {
+    MockTransport::new()
+  }
+}
+
+impl MockTransport {
+  pub fn new() -> MockTransport {
+    MockTransport {
+      platform: MockPlatform::default(),
+    }
+  }
+
+  pub fn token(&self) -> String {
+    mint_token(MOCK_TRANSPORT_URL, MOCK_OWNER, MOCK_PROJECT)
+  }
+
+  pub fn client_builder(&self) -> ClientBuilder {
+    let mut client_builder = ClientBuilder::new(self.token());
+    client_builder.set_transport(self.clone());
+    client_builder
+  }
+
+  pub fn client(&self) -> Result<Client, Error> {
+    self.client_builder().create_client()
+  }
+
+  pub fn platform(&self) -> &MockPlatform {
+    &self.platform
+  }
+}
+
+impl Transport for MockTransport {
+  fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>> {
+    Box::pin(async move {
+      let HttpRequest {
+        method,
+        url,
+        headers,
+        body,
+      } = request;
+      let mut hyper_request = Request::builder()
+        .method(method)
+        .uri(url.as_str())
+        .body(Body::from(body.unwrap_or_default()))
+        .map_err(|err| network_error(&url, err))?;
+      let host = hyper_request
+        .uri()
+        .authority()
+        .map(|authority| authority.as_str().to_string())
+        .unwrap_or_default();
+      *hyper_request.headers_mut() = headers;
+      hyper_request
+        .headers_mut()
+        .insert(HOST, host.parse().map_err(|err| network_error(&url, err))?);
+      let response = match handle_request(self.platform.state.clone(), hyper_request).await {
+        Ok(response) => response,
+        Err(infallible) => match infallible {},
+      };
+      let (parts, body) = response.into_parts();
+      let body = hyper::body::to_bytes(body)
+        .await
+        .map_err(|err| network_error(&url, err))?;
+      Ok(HttpResponse {
+        status: parts.status,
+        headers: parts.headers,
+        body: body.to_vec(),
+      })
+    })
+  }
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+  Response::builder()
+    .status(status)
+    .header("content-type", "application/json")
+    .body(Body::from(body.to_string()))
+    .unwrap()
+}
+
+fn error_response(status: StatusCode, message: String) -> Response<Body> {
+  json_response(status, json!({ "message": message }))
+}
+
+// Pages of entity names are wrapped in an object under `list_key`, other pages are bare arrays
+fn page_response<T: serde::Serialize>(
+  url: &str,
+  query: &BTreeMap<String, String>,
+  items: &[T],
+  page_size: usize,
+  list_key: Option<&str>,
+) -> Response<Body> {
+  let page: usize = query
+    .get("page")
+    .and_then(|page| page.parse().ok())
+    .unwrap_or(0);
+  let start = (page * page_size).min(items.len());
+  let end = (start + page_size).min(items.len());
+  let page_items = json!(&items[start..end]);
+  let mut response = json_response(
+    StatusCode::OK,
+    match list_key {
+      Some(list_key) => json!({ list_key: page_items }),
+      None => page_items,
+    },
+  );
+  if end < items.len() {
+    let mut next_query: Vec<String> = query
+      .iter()
+      .filter(|(key, _)| key.as_str() != "page")
+      .map(|(key, value)| format!("{}={}", key, value))
+      .collect();
+    next_query.push(format!("page={}", page + 1));
+    response.headers_mut().insert(
+      "x-craft-ai-next-page-url",
+      format!("{}?{}", url, next_query.join("&")).parse().unwrap(),
+    );
+  }
+  response
+}
+
+fn parse_query(query: Option<&str>) -> BTreeMap<String, String> {
+  query
+    .unwrap_or_default()
+    .split('&')
+    .filter_map(|pair| {
+      let mut key_value = pair.splitn(2, '=');
+      match (key_value.next(), key_value.next()) {
+        (Some(key), Some(value)) if !key.is_empty() => Some((key.to_string(), value.to_string())),
+        _ => None,
+      }
+    })
+    .collect()
+}
+
+fn query_bound(query: &BTreeMap<String, String>, key: &str) -> Option<u64> {
+  query.get(key).and_then(|bound| bound.parse().ok())
+}
+
+fn is_within_bounds(timestamp: u64, lower_bound: Option<u64>, upper_bound: Option<u64>) -> bool {
+  lower_bound.unwrap_or(u64::MIN) <= timestamp && timestamp <= upper_bound.unwrap_or(u64::MAX)
+}
+
+fn agent_json(name: &str, agent: &MockAgent) -> Value {
+  let mut agent_json = json!({
+    "id": name,
+    "configuration": agent.configuration,
+  });
+  if let (Some(first), Some(last)) = (agent.operations.first(), agent.operations.last()) {
+    agent_json["firstTimestamp"] = json!(first.timestamp);
+    agent_json["lastTimestamp"] = json!(last.timestamp);
+  }
+  agent_json
+}
+
+fn generator_json(name: &str, generator: &MockGenerator) -> Value {
+  json!({
+    "id": name,
+    "configuration": generator.configuration,
+  })
+}
+
+// The mock doesn't learn anything, its trees hold a single leaf predicting the last known output.
+fn decision_tree_json<'a, OperationsT: DoubleEndedIterator<Item = (u64, &'a Context)>>(
+  configuration: Value,
+  output: &str,
+  operations: OperationsT,
+  timestamp: Option<u64>,
+  version: Option<&str>,
+) -> Value {
+  let predicted_value = operations
+    .rev()
+    .filter(|(operation_timestamp, _)| is_within_bounds(*operation_timestamp, None, timestamp))
+    .find_map(|(_, context)| context.get(output))
+    .cloned()
+    .unwrap_or(Value::Null);
+  let (version, leaf) = match version {
+    Some("1") => (
+      "1.1.0",
+      json!({"predicted_value": predicted_value, "confidence": 1}),
+    ),
+    _ => (
+      "2.0.0",
+      json!({"prediction": {"value": predicted_value, "confidence": 1}}),
+    ),
+  };
+  let mut trees = serde_json::Map::new();
+  trees.insert(output.to_string(), leaf);
+  json!({
+    "_version": version,
+    "configuration": configuration,
+    "trees": trees,
+  })
+}
+
+fn generator_operations<'a>(
+  state: &'a MockState,
+  name: &str,
+) -> Result<(&'a MockGenerator, Vec<GeneratorContextOperation>), (StatusCode, String)> {
+  let generator = state.generators.get(name).ok_or_else(|| {
+    (
+      StatusCode::NOT_FOUND,
+      format!("Generator '{}' does not exist", name),
+    )
+  })?;
+  let mut operations: Vec<GeneratorContextOperation> = generator
+    .configuration
+    .filter
+    .iter()
+    .filter_map(|agent_name| {
+      state
+        .agents
+        .get(agent_name)
+        .map(|agent| (agent_name, agent))
+    })
+    .flat_map(|(agent_name, agent)| {
+      agent
+        .operations
+        .iter()
+        .map(move |operation| GeneratorContextOperation {
+          agent_name: agent_name.clone(),
+          timestamp: operation.timestamp,
+          context: operation.context.clone(),
+        })
+    })
+    .collect();
+  operations.sort_by_key(|operation| operation.timestamp);
+  Ok((generator, operations))
+}
+
+#[derive(Deserialize)]
+struct CreateReqBody<ConfigurationT> {
+  id: Option<String>,
+  configuration: ConfigurationT,
+}
+
+#[derive(Deserialize)]
+struct DeleteReqBody {
+  id: String,
+}
+
+fn parse_body<T: for<'de> Deserialize<'de>>(body: &[u8]) -> Result<T, (StatusCode, String)> {
+  serde_json::from_slice(body).map_err(|err| {
+    (
+      StatusCode::BAD_REQUEST,
+      format!("Unable to parse request body: {}", err),
+    )
+  })
+}
+
+fn create_agent(
+  state: &mut MockState,
+  name: Option<String>,
+  configuration: AgentConfiguration,
+) -> Result<Value, (StatusCode, String)> {
+  let name = name.unwrap_or_else(|| {
+    state.generated_names_count += 1;
+    format!("generated-agent-{}", state.generated_names_count)
+  });
+  if state.agents.contains_key(&name) {
+    return Err((
+      StatusCode::CONFLICT,
+      format!("An agent named '{}' already exists", name),
+    ));
+  }
+  if !configuration.context.contains_key(&configuration.output[0]) {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      format!(
+        "Output property '{}' is not defined in the context",
+        configuration.output[0]
+      ),
+    ));
+  }
+  let agent = MockAgent {
+    configuration,
+    operations: Vec::new(),
+  };
+  let agent_json = agent_json(&name, &agent);
+  state.agents.insert(name, agent);
+  Ok(agent_json)
+}
+
+fn handle_project_request(
+  state: &mut MockState,
+  method: &Method,
+  url: &str,
+  path: &[&str],
+  query: &BTreeMap<String, String>,
+  tree_version: Option<&str>,
+  body: &[u8],
+) -> Result<Response<Body>, (StatusCode, String)> {
+  match (method, path) {
+    (&Method::POST, ["agents"]) => {
+      let req_body: CreateReqBody<AgentConfiguration> = parse_body(body)?;
+      create_agent(state, req_body.id, req_body.configuration)
+        .map(|agent_json| json_response(StatusCode::CREATED, agent_json))
+    }
+    (&Method::GET, ["agents"]) => {
+      let names: Vec<&String> = state.agents.keys().collect();
+      Ok(page_response(
+        url,
+        query,
+        &names,
+        PAGE_SIZE,
+        Some("agentsList"),
+      ))
+    }
+    (&Method::GET, ["agents", name]) => match state.agents.get(*name) {
+      Some(agent) => Ok(json_response(StatusCode::OK, agent_json(name, agent))),
+      None => Err((
+        StatusCode::NOT_FOUND,
+        format!("Agent '{}' does not exist", name),
+      )),
+    },
+    (&Method::DELETE, ["agents", name]) => match state.agents.remove(*name) {
+      Some(agent) => Ok(json_response(StatusCode::OK, agent_json(name, &agent))),
+      None => Ok(error_response(
+        StatusCode::OK,
+        format!("Agent '{}' does not exist", name),
+      )),
+    },
+    (&Method::POST, ["agents", name, "context"]) => {
+      let operations: Vec<ContextOperation> = parse_body(body)?;
+      let agent = state.agents.get_mut(*name).ok_or_else(|| {
+        (
+          StatusCode::NOT_FOUND,
+          format!("Agent '{}' does not exist", name),
+        )
+      })?;
+      for operation in operations.iter() {
+        operation
+          .check(&agent.configuration)
+          .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+      }
+      let operations_count = operations.len();
+      agent.operations.extend(operations);
+      agent
+        .operations
+        .sort_by_key(|operation| operation.timestamp);
+      Ok(json_response(
+        StatusCode::CREATED,
+        json!({
+          "message": format!("{} operations added to agent '{}'", operations_count, name)
+        }),
+      ))
+    }
+    (&Method::GET, ["agents", name, "context"]) => {
+      let agent = state.agents.get(*name).ok_or_else(|| {
+        (
+          StatusCode::NOT_FOUND,
+          format!("Agent '{}' does not exist", name),
+        )
+      })?;
+      let lower_bound = query_bound(query, "lowerBound");
+      let upper_bound = query_bound(query, "upperBound");
+      let operations: Vec<&ContextOperation> = agent
+        .operations
+        .iter()
+        .filter(|operation| is_within_bounds(operation.timestamp, lower_bound, upper_bound))
+        .collect();
+      Ok(page_response(
+        url,
+        query,
+        &operations,
+        CONTEXT_OPERATIONS_PAGE_SIZE,
+        None,
+      ))
+    }
+    (&Method::GET, ["agents", name, "context", "state"]) => {
+      let agent = state.agents.get(*name).ok_or_else(|| {
+        (
+          StatusCode::NOT_FOUND,
+          format!("Agent '{}' does not exist", name),
+        )
+      })?;
+      let timestamp = query_bound(query, "t");
+      let operations: Vec<&ContextOperation> = agent
+        .operations
+        .iter()
+        .filter(|operation| is_within_bounds(operation.timestamp, None, timestamp))
+        .collect();
+      let last_operation = operations.last().ok_or_else(|| {
+        (
+          StatusCode::BAD_REQUEST,
+          format!(
+            "No context operation before the given timestamp for agent '{}'",
+            name
+          ),
+        )
+      })?;
+      let mut context = Context::new();
+      for operation in operations.iter() {
+        context.extend(operation.context.clone());
+      }
+      Ok(json_response(
+        StatusCode::OK,
+        json!({"timestamp": last_operation.timestamp, "context": context}),
+      ))
+    }
+    (&Method::GET, ["agents", name, "decision", "tree"]) => {
+      let agent = state.agents.get(*name).ok_or_else(|| {
+        (
+          StatusCode::NOT_FOUND,
+          format!("Agent '{}' does not exist", name),
+        )
+      })?;
+      Ok(json_response(
+        StatusCode::OK,
+        decision_tree_json(
+          json!(agent.configuration),
+          &agent.configuration.output[0],
+          agent
+            .operations
+            .iter()
+            .map(|operation| (operation.timestamp, &operation.context)),
+          query_bound(query, "t"),
+          tree_version,
+        ),
+      ))
+    }
+    (&Method::POST, ["bulk", "agents"]) => {
+      let req_body: Vec<CreateReqBody<AgentConfiguration>> = parse_body(body)?;
+      let res_body: Vec<Value> = req_body
+        .into_iter()
+        .map(|agent_req_body| {
+          let name = agent_req_body.id.clone();
+          create_agent(state, agent_req_body.id, agent_req_body.configuration).unwrap_or_else(
+            |(status, message)| json!({"id": name, "status": status.as_u16(), "message": message}),
+          )
+        })
+        .collect();
+      Ok(json_response(StatusCode::OK, json!(res_body)))
+    }
+    (&Method::DELETE, ["bulk", "agents"]) => {
+      let req_body: Vec<DeleteReqBody> = parse_body(body)?;
+      let res_body: Vec<Value> = req_body
+        .into_iter()
+        .map(
+          |agent_req_body| match state.agents.remove(&agent_req_body.id) {
+            Some(agent) => agent_json(&agent_req_body.id, &agent),
+            None => json!({
+              "id": agent_req_body.id,
+              "status": 404,
+              "message": format!("Agent '{}' does not exist", agent_req_body.id)
+            }),
+          },
+        )
+        .collect();
+      Ok(json_response(StatusCode::OK, json!(res_body)))
+    }
+    (&Method::POST, ["generators"]) => {
+      let req_body: CreateReqBody<GeneratorConfiguration> = parse_body(body)?;
+      let name = req_body.id.ok_or_else(|| {
+        (
+          StatusCode::BAD_REQUEST,
+          "Generators must be named".to_string(),
+        )
+      })?;
+      if state.generators.contains_key(&name) {
+        return Err((
+          StatusCode::CONFLICT,
+          format!("A generator named '{}' already exists", name),
+        ));
+      }
+      let generator = MockGenerator {
+        configuration: req_body.configuration,
+      };
+      let generator_json = generator_json(&name, &generator);
+      state.generators.insert(name, generator);
+      Ok(json_response(StatusCode::CREATED, generator_json))
+    }
+    (&Method::GET, ["generators"]) => {
+      let names: Vec<&String> = state.generators.keys().collect();
+      Ok(page_response(
+        url,
+        query,
+        &names,
+        PAGE_SIZE,
+        Some("generatorsList"),
+      ))
+    }
+    (&Method::GET, ["generators", name]) => match state.generators.get(*name) {
+      Some(generator) => Ok(json_response(
+        StatusCode::OK,
+        generator_json(name, generator),
+      )),
+      None => Err((
+        StatusCode::NOT_FOUND,
+        format!("Generator '{}' does not exist", name),
+      )),
+    },
+    (&Method::DELETE, ["generators", name]) => match state.generators.remove(*name) {
+      Some(generator) => Ok(json_response(
+        StatusCode::OK,
+        generator_json(name, &generator),
+      )),
+      None => Ok(error_response(
+        StatusCode::OK,
+        format!("Generator '{}' does not exist", name),
+      )),
+    },
+    (&Method::GET, ["generators", name, "context"]) => {
+      let (_, mut operations) = generator_operations(state, name)?;
+      let lower_bound = query_bound(query, "lowerBound");
+      let upper_bound = query_bound(query, "upperBound");
+      operations
+        .retain(|operation| is_within_bounds(operation.timestamp, lower_bound, upper_bound));
+      Ok(page_response(
+        url,
+        query,
+        &operations,
+        CONTEXT_OPERATIONS_PAGE_SIZE,
+        None,
+      ))
+    }
+    (&Method::GET, ["generators", name, "decision", "tree"]) => {
+      let (generator, operations) = generator_operations(state, name)?;
+      Ok(json_response(
+        StatusCode::OK,
+        decision_tree_json(
+          json!(generator.configuration),
+          &generator.configuration.output[0],
+          operations
+            .iter()
+            .map(|operation| (operation.timestamp, &operation.context)),
+          query_bound(query, "t"),
+          tree_version,
+        ),
+      ))
+    }
+    _ => Err((
+      StatusCode::NOT_FOUND,
+      format!("Unknown route {} '{}'", method, url),
+    )),
+  }
+}
+
+async fn handle_request(
+  state: Arc<Mutex<MockState>>,
+  request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+  let (parts, body) = request.into_parts();
+  let body = match hyper::body::to_bytes(body).await {
+    Ok(body) => body,
+    Err(err) => {
+      return Ok(error_response(
+        StatusCode::BAD_REQUEST,
+        format!("Unable to read request body: {}", err),
+      ))
+    }
+  };
+  {
+    let mut state = state.lock().unwrap();
+    state.requests_count += 1;
+    if let Some(failure) = state.failures.pop_front() {
+      return Ok(failure.response());
+    }
+  }
+  let host = parts
+    .headers
+    .get("host")
+    .and_then(|host| host.to_str().ok())
+    .unwrap_or_default();
+  let url = format!("http://{}{}", host, parts.uri.path());
+  let expected_authorization = format!(
+    "Bearer {}",
+    mint_token(&format!("http://{}", host), MOCK_OWNER, MOCK_PROJECT)
+  );
+  let authorization = parts
+    .headers
+    .get("authorization")
+    .and_then(|authorization| authorization.to_str().ok());
+  if authorization != Some(expected_authorization.as_str()) {
+    return Ok(error_response(
+      StatusCode::UNAUTHORIZED,
+      "Invalid or missing token".to_string(),
+    ));
+  }
+  let query = parse_query(parts.uri.query());
+  let tree_version = parts
+    .headers
+    .get("x-craft-ai-tree-version")
+    .and_then(|version| version.to_str().ok());
+  let path: Vec<&str> = parts
+    .uri
+    .path()
+    .split('/')
+    .filter(|segment| !segment.is_empty())
+    .collect();
+  let response = match path.as_slice() {
+    ["api", "v1"] => json_response(
+      StatusCode::OK,
+      json!({"craft_version": "mock", "activated_features": []}),
+    ),
+    ["api", "v1", owner, project, project_path @ ..]
+      if *owner == MOCK_OWNER && *project == MOCK_PROJECT =>
+    {
+      let mut state = state.lock().unwrap();
+      if parts.method == Method::GET
+        && project_path.ends_with(&["decision", "tree"])
+        && state.pending_computations > 0
+      {
+        state.pending_computations -= 1;
+        let location = state.computations_location.clone().unwrap_or_else(|| {
+          parts
+            .uri
+            .path_and_query()
+            .map_or_else(String::new, |path_and_query| path_and_query.to_string())
+        });
+        let mut response = json_response(
+          StatusCode::ACCEPTED,
+          json!({"message": "Computation in progress"}),
+        );
+        response
+          .headers_mut()
+          .insert(LOCATION, location.parse().unwrap());
+        return Ok(response);
+      }
+      handle_project_request(
+        &mut state,
+        &parts.method,
+        &url,
+        project_path,
+        &query,
+        tree_version,
+        &body,
+      )
+      .unwrap_or_else(|(status, message)| error_response(status, message))
+    }
+    _ => error_response(
+      StatusCode::NOT_FOUND,
+      format!("Unknown route {} '{}'", parts.method, url),
+    ),
+  };
+  Ok(response)
+}
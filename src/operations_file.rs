@@ -0,0 +1,483 @@
+use crate::agent::{get_agent, upload_agent_context_operations};
+use crate::client::Client;
+use crate::error::Error;
+use crate::types::{AgentConfiguration, ContextOperation};
+#[cfg(feature = "csv")]
+use crate::types::{Context, PropertyType};
+use futures::executor::block_on;
+#[cfg(feature = "csv")]
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use tokio::sync::mpsc;
+use tokio::task::{self, JoinError};
+
+// Operations are sorted by batches of this size before being uploaded, a file can therefore be
+// out of order as long as no operation is older than the ones of a previously uploaded batch.
+// Files are checked as a whole before the first upload, readers are checked batch by batch.
+const OPERATIONS_SORT_WINDOW: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OperationsFileFormat {
+  // A `timestamp` column followed by one column per property, empty cells are skipped
+  Csv,
+  // One serialized `ContextOperation` per line
+  Ndjson,
+}
+
+impl OperationsFileFormat {
+  pub fn from_path<P: AsRef<Path>>(path: P) -> Result<OperationsFileFormat, Error> {
+    let path = path.as_ref();
+    match path.extension().and_then(|extension| extension.to_str()) {
+      Some("csv") => Ok(OperationsFileFormat::Csv),
+      Some("ndjson") | Some("jsonl") => Ok(OperationsFileFormat::Ndjson),
+      _ => Err(Error::InvalidArgument(format!(
+        "Unable to guess the format of '{}', expected a .csv, .ndjson or .jsonl file",
+        path.display()
+      ))),
+    }
+  }
+}
+
+type Operations<'a> = Box<dyn Iterator<Item = Result<ContextOperation, Error>> + Send + 'a>;
+
+#[cfg(feature = "csv")]
+fn parse_csv_value(property_type: &PropertyType, cell: &str) -> Option<Value> {
+  if cell == "null" {
+    return Some(Value::Null);
+  }
+  match property_type {
+    PropertyType::Continuous | PropertyType::TimeOfDay | PropertyType::Periodic => {
+      cell.parse::<f64>().ok().map(Value::from)
+    }
+    PropertyType::DayOfWeek | PropertyType::DayOfMonth | PropertyType::MonthOfYear => {
+      cell.parse::<u64>().ok().map(Value::from)
+    }
+    PropertyType::Boolean => cell.parse::<bool>().ok().map(Value::from),
+    PropertyType::Enum | PropertyType::Timezone => Some(Value::from(cell)),
+  }
+}
+
+#[cfg(feature = "csv")]
+fn read_csv_operations<'a, R: Read + Send + 'a>(
+  reader: R,
+  configuration: &'a AgentConfiguration,
+) -> Result<Operations<'a>, Error> {
+  let mut csv_reader = csv::ReaderBuilder::new()
+    .trim(csv::Trim::All)
+    .from_reader(reader);
+  let headers = csv_reader
+    .headers()
+    .map_err(|err| Error::InvalidArgument(format!("Unable to read the csv header ({})", err)))?;
+  let timestamp_index = headers
+    .iter()
+    .position(|header| header == "timestamp")
+    .ok_or_else(|| {
+      Error::InvalidArgument("Missing 'timestamp' column in csv header".to_string())
+    })?;
+  let columns = headers
+    .iter()
+    .enumerate()
+    .filter(|(index, _)| *index != timestamp_index)
+    .map(|(index, header)| match configuration.context.get(header) {
+      Some(property) => Ok((index, header.to_string(), property.property_type.clone())),
+      None => Err(Error::InvalidArgument(format!(
+        "Unknown property '{}' in csv header",
+        header
+      ))),
+    })
+    .collect::<Result<Vec<_>, Error>>()?;
+  Ok(Box::new(csv_reader.into_records().map(move |record| {
+    let record = record
+      .map_err(|err| Error::InvalidArgument(format!("Unable to read csv record ({})", err)))?;
+    let line = record.position().map_or(0, |position| position.line());
+    let timestamp = record
+      .get(timestamp_index)
+      .and_then(|cell| cell.parse::<u64>().ok())
+      .ok_or_else(|| Error::InvalidArgument(format!("Invalid timestamp at line {}", line)))?;
+    let mut context = Context::new();
+    for (index, property_name, property_type) in columns.iter() {
+      let cell = record.get(*index).unwrap_or_default();
+      if cell.is_empty() {
+        continue;
+      }
+      let value = parse_csv_value(property_type, cell).ok_or_else(|| {
+        Error::InvalidArgument(format!(
+          "Invalid value '{}' for property '{}' of type '{}' at line {}",
+          cell, property_name, property_type, line
+        ))
+      })?;
+      context.insert(property_name.clone(), value);
+    }
+    Ok(ContextOperation::new(timestamp, context))
+  })))
+}
+
+fn read_ndjson_operations<'a, R: Read + Send + 'a>(reader: R) -> Operations<'a> {
+  Box::new(
+    BufReader::new(reader)
+      .lines()
+      .enumerate()
+      .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+      .map(|(index, line)| {
+        let line = line.map_err(|err| {
+          Error::InvalidArgument(format!("Unable to read line {} ({})", index + 1, err))
+        })?;
+        serde_json::from_str::<ContextOperation>(&line).map_err(|err| {
+          Error::InvalidArgument(format!(
+            "Unable to deserialize context operation at line {} ({})",
+            index + 1,
+            err
+          ))
+        })
+      }),
+  )
+}
+
+#[cfg_attr(not(feature = "csv"), allow(unused_variables))]
+fn read_operations<'a, R: Read + Send + 'a>(
+  reader: R,
+  format: OperationsFileFormat,
+  configuration: &'a AgentConfiguration,
+) -> Result<Operations<'a>, Error> {
+  match format {
+    #[cfg(feature = "csv")]
+    OperationsFileFormat::Csv => read_csv_operations(reader, configuration),
+    #[cfg(not(feature = "csv"))]
+    OperationsFileFormat::Csv => Err(Error::InvalidArgument(
+      "Reading csv operations files requires the 'csv' feature".to_string(),
+    )),
+    OperationsFileFormat::Ndjson => Ok(read_ndjson_operations(reader)),
+  }
+}
+
+// Operations of a batch are sorted before being uploaded but can't precede already uploaded ones
+fn check_operation(
+  operation: &ContextOperation,
+  configuration: &AgentConfiguration,
+  last_uploaded_timestamp: Option<u64>,
+) -> Result<(), Error> {
+  operation.check(configuration)?;
+  match last_uploaded_timestamp {
+    Some(last_uploaded_timestamp) if operation.timestamp < last_uploaded_timestamp => {
+      Err(Error::InvalidArgument(format!(
+        "Context operation at timestamp {} is older than already uploaded ones, the file should be sorted by timestamp",
+        operation.timestamp
+      )))
+    }
+    _ => Ok(()),
+  }
+}
+
+pub(crate) fn open_operations_file(path: &Path) -> Result<(File, OperationsFileFormat), Error> {
+  let format = OperationsFileFormat::from_path(path)?;
+  let file = File::open(path)
+    .map_err(|_err| Error::InvalidArgument(format!("Unable to open '{}'.", path.display())))?;
+  Ok((file, format))
+}
+
+// Reads operations by batches of `OPERATIONS_SORT_WINDOW`, each batch is checked against the agent
+// configuration and sorted. It's shared by the async and blocking uploads which upload a batch
+// before reading the next one, no operation can thus precede the previous batch.
+pub(crate) struct OperationsBatches<'a> {
+  operations: Operations<'a>,
+  configuration: &'a AgentConfiguration,
+  last_timestamp: Option<u64>,
+}
+
+impl<'a> OperationsBatches<'a> {
+  pub(crate) fn new<R: Read + Send + 'a>(
+    reader: R,
+    format: OperationsFileFormat,
+    configuration: &'a AgentConfiguration,
+  ) -> Result<OperationsBatches<'a>, Error> {
+    Ok(OperationsBatches {
+      operations: read_operations(reader, format, configuration)?,
+      configuration,
+      last_timestamp: None,
+    })
+  }
+
+  fn next_batch(&mut self) -> Result<Vec<ContextOperation>, Error> {
+    let mut batch = Vec::with_capacity(OPERATIONS_SORT_WINDOW);
+    while batch.len() < OPERATIONS_SORT_WINDOW {
+      let operation = match self.operations.next() {
+        Some(operation) => operation?,
+        None => break,
+      };
+      check_operation(&operation, self.configuration, self.last_timestamp)?;
+      batch.push(operation);
+    }
+    batch.sort_by_key(|operation| operation.timestamp);
+    if let Some(operation) = batch.last() {
+      self.last_timestamp = Some(operation.timestamp);
+    }
+    Ok(batch)
+  }
+}
+
+impl Iterator for OperationsBatches<'_> {
+  type Item = Result<Vec<ContextOperation>, Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match self.next_batch() {
+      Ok(batch) if batch.is_empty() => None,
+      result => Some(result),
+    }
+  }
+}
+
+// Counts the uploaded operations for the async and blocking uploads, errors report how many were
+// uploaded before them
+pub(crate) struct UploadProgress<ProgressT> {
+  uploaded_count: usize,
+  on_progress: ProgressT,
+}
+
+impl<ProgressT: FnMut(usize)> UploadProgress<ProgressT> {
+  pub(crate) fn new(on_progress: ProgressT) -> UploadProgress<ProgressT> {
+    UploadProgress {
+      uploaded_count: 0,
+      on_progress,
+    }
+  }
+
+  pub(crate) fn uploaded_count(&self) -> usize {
+    self.uploaded_count
+  }
+
+  pub(crate) fn failed(&self, err: Error) -> Error {
+    err.partially_uploaded(self.uploaded_count)
+  }
+
+  pub(crate) fn uploaded(&mut self, batch: &[ContextOperation]) {
+    self.uploaded_count += batch.len();
+    (self.on_progress)(self.uploaded_count);
+  }
+}
+
+// Reads the whole file without uploading anything, invalid or unsorted files are thus rejected
+// before the first upload
+pub(crate) fn check_operations_file(
+  path: &Path,
+  configuration: &AgentConfiguration,
+) -> Result<(), Error> {
+  let (file, format) = open_operations_file(path)?;
+  OperationsBatches::new(file, format, configuration)?.try_for_each(|batch| batch.map(|_| ()))
+}
+
+fn reading_error(err: JoinError) -> Error {
+  Error::InternalError(
+    format!("Unable to read the context operations ({})", err),
+    None,
+  )
+}
+
+// Reading is blocking, it's done on a dedicated thread sending the batches to upload one at a time
+async fn upload_operations<R: Read + Send + 'static, ProgressT: FnMut(usize)>(
+  client: &Client,
+  name: &str,
+  configuration: AgentConfiguration,
+  reader: R,
+  format: OperationsFileFormat,
+  on_progress: ProgressT,
+) -> Result<usize, Error> {
+  let (mut batches_sender, mut batches_receiver) = mpsc::channel(1);
+  let reading = task::spawn_blocking(move || {
+    let batches = match OperationsBatches::new(reader, format, &configuration) {
+      Ok(batches) => batches,
+      Err(err) => {
+        block_on(batches_sender.send(Err(err))).ok();
+        return;
+      }
+    };
+    for batch in batches {
+      // The receiver is dropped when an upload fails
+      if block_on(batches_sender.send(batch)).is_err() {
+        return;
+      }
+    }
+  });
+  let mut progress = UploadProgress::new(on_progress);
+  while let Some(batch) = batches_receiver.recv().await {
+    let batch = batch.map_err(|err| progress.failed(err))?;
+    upload_agent_context_operations(client, name, &batch)
+      .await
+      .map_err(|err| progress.failed(err))?;
+    progress.uploaded(&batch);
+  }
+  reading.await.map_err(reading_error)?;
+  Ok(progress.uploaded_count())
+}
+
+// `on_progress` is called with the total count of uploaded operations after each batch. A reader
+// can't be read twice, operations older than an already uploaded batch of `OPERATIONS_SORT_WINDOW`
+// operations, or invalid ones, fail the upload with `Error::PartiallyUploaded`.
+pub async fn add_agent_context_operations_from_reader<
+  T: Into<String>,
+  R: Read + Send + 'static,
+  ProgressT: FnMut(usize),
+>(
+  client: &Client,
+  name: T,
+  reader: R,
+  format: OperationsFileFormat,
+  on_progress: ProgressT,
+) -> Result<usize, Error> {
+  let name = name.into();
+  let configuration = get_agent(client, name.as_str()).await?.configuration;
+  upload_operations(client, &name, configuration, reader, format, on_progress).await
+}
+
+pub async fn add_agent_context_operations_from_file<
+  T: Into<String>,
+  P: AsRef<Path>,
+  ProgressT: FnMut(usize),
+>(
+  client: &Client,
+  name: T,
+  path: P,
+  on_progress: ProgressT,
+) -> Result<usize, Error> {
+  let name = name.into();
+  let configuration = get_agent(client, name.as_str()).await?.configuration;
+  let path = path.as_ref().to_path_buf();
+  let checked_configuration = configuration.clone();
+  let (file, format) = task::spawn_blocking(move || {
+    check_operations_file(&path, &checked_configuration)?;
+    open_operations_file(&path)
+  })
+  .await
+  .map_err(reading_error)??;
+  upload_operations(client, &name, configuration, file, format, on_progress).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::types::{ConfigurationBuilder, PropertyType};
+  #[cfg(feature = "csv")]
+  use serde_json::json;
+
+  fn configuration() -> AgentConfiguration {
+    ConfigurationBuilder::new()
+      .add_property("day", PropertyType::DayOfWeek, None, None)
+      .add_property("presence", PropertyType::Enum, None, Some(true))
+      .add_property("value", PropertyType::Continuous, None, None)
+      .set_output_property("value")
+      .create_agent_configuration()
+      .unwrap()
+  }
+
+  #[cfg(feature = "csv")]
+  #[test]
+  fn read_csv() {
+    let configuration = configuration();
+    let csv = "value, timestamp,day,presence\n3.5,1464600000,2,home\n4,1464600060,,null\n";
+    let operations = read_csv_operations(csv.as_bytes(), &configuration)
+      .unwrap()
+      .collect::<Result<Vec<_>, Error>>()
+      .unwrap();
+    assert_eq!(
+      serde_json::to_value(&operations).unwrap(),
+      json!([
+        {"timestamp": 1_464_600_000, "context": {"value": 3.5, "day": 2, "presence": "home"}},
+        {"timestamp": 1_464_600_060, "context": {"value": 4.0, "presence": null}}
+      ])
+    );
+  }
+
+  #[cfg(feature = "csv")]
+  #[test]
+  fn read_csv_invalid() {
+    let configuration = configuration();
+    let error = read_csv_operations("value,day\n3,2\n".as_bytes(), &configuration)
+      .err()
+      .unwrap();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Missing 'timestamp' column in csv header"
+    );
+    let error = read_csv_operations("timestamp,foo\n3,2\n".as_bytes(), &configuration)
+      .err()
+      .unwrap();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Unknown property 'foo' in csv header"
+    );
+    let error = read_csv_operations(
+      "timestamp,value\n1464600000,1\n1464600060,high\n".as_bytes(),
+      &configuration,
+    )
+    .unwrap()
+    .collect::<Result<Vec<_>, Error>>()
+    .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Invalid value 'high' for property 'value' of type 'continuous' at line 3"
+    );
+  }
+
+  #[test]
+  fn read_ndjson() {
+    let ndjson = "{\"timestamp\":1464600000,\"context\":{\"value\":3}}\n\n{\"timestamp\":1464600060,\"context\":{}}\n";
+    let operations = read_ndjson_operations(ndjson.as_bytes())
+      .collect::<Result<Vec<_>, Error>>()
+      .unwrap();
+    assert_eq!(operations.len(), 2);
+    assert_eq!(operations[1].timestamp, 1_464_600_060);
+    let error = read_ndjson_operations("{\"timestamp\":1}\n".as_bytes())
+      .collect::<Result<Vec<_>, Error>>()
+      .unwrap_err();
+    assert!(error
+      .to_string()
+      .starts_with("Invalid argument - Unable to deserialize context operation at line 1"));
+  }
+
+  #[test]
+  fn format_from_path() {
+    assert_eq!(
+      OperationsFileFormat::from_path("history.csv").unwrap(),
+      OperationsFileFormat::Csv
+    );
+    assert_eq!(
+      OperationsFileFormat::from_path("history.jsonl").unwrap(),
+      OperationsFileFormat::Ndjson
+    );
+    assert!(OperationsFileFormat::from_path("history.txt").is_err());
+  }
+
+  #[test]
+  fn operations_batches() {
+    let configuration = configuration();
+    // the first batch is sorted, the last operation precedes it
+    let ndjson: String = (0..OPERATIONS_SORT_WINDOW as u64)
+      .rev()
+      .chain(vec![OPERATIONS_SORT_WINDOW as u64, 0])
+      .map(|index| {
+        format!(
+          "{{\"timestamp\":{},\"context\":{{\"value\":1}}}}\n",
+          1_464_600_000 + index
+        )
+      })
+      .collect();
+    let mut batches = OperationsBatches::new(
+      ndjson.as_bytes(),
+      OperationsFileFormat::Ndjson,
+      &configuration,
+    )
+    .unwrap();
+    let batch = batches.next().unwrap().unwrap();
+    assert_eq!(batch.len(), OPERATIONS_SORT_WINDOW);
+    assert_eq!(batch[0].timestamp, 1_464_600_000);
+    assert_eq!(
+      batch[OPERATIONS_SORT_WINDOW - 1].timestamp,
+      1_464_600_000 + OPERATIONS_SORT_WINDOW as u64 - 1
+    );
+    assert_eq!(
+      batches.next().unwrap().unwrap_err().to_string(),
+      "Invalid argument - Context operation at timestamp 1464600000 is older than already uploaded ones, the file should be sorted by timestamp"
+    );
+  }
+}
@@ -0,0 +1,154 @@
+use craft_ai::Error;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+  Json,
+  Yaml,
+  Table,
+  Quiet,
+}
+
+impl OutputFormat {
+  pub fn from_name(name: &str) -> Result<OutputFormat, Error> {
+    match name {
+      "json" => Ok(OutputFormat::Json),
+      "yaml" => Ok(OutputFormat::Yaml),
+      "table" => Ok(OutputFormat::Table),
+      "quiet" => Ok(OutputFormat::Quiet),
+      _ => Err(Error::InvalidArgument(format!(
+        "Unknown output format '{}'",
+        name
+      ))),
+    }
+  }
+
+  // Renders the given document, `None` means nothing should be written to stdout
+  pub fn render<ContentT: Serialize>(self, content: &ContentT) -> Result<Option<String>, Error> {
+    let serialization_error = |err: Box<dyn std::error::Error + Send + Sync>| {
+      Error::InternalError("Unable to serialize the result".to_string(), Some(err))
+    };
+    match self {
+      OutputFormat::Json => serde_json::to_string_pretty(content)
+        .map(Some)
+        .map_err(|err| serialization_error(Box::new(err))),
+      OutputFormat::Yaml => serde_yaml::to_string(content)
+        .map(Some)
+        .map_err(|err| serialization_error(Box::new(err))),
+      OutputFormat::Table => serde_json::to_value(content)
+        .map(|value| Some(render_table(&value)))
+        .map_err(|err| serialization_error(Box::new(err))),
+      OutputFormat::Quiet => Ok(None),
+    }
+  }
+}
+
+fn render_cell(value: &Value) -> String {
+  match value {
+    Value::String(string) => string.clone(),
+    Value::Null => String::new(),
+    _ => value.to_string(),
+  }
+}
+
+fn render_rows(rows: &[Vec<String>]) -> String {
+  let columns_count = rows.iter().map(Vec::len).max().unwrap_or_default();
+  let widths: Vec<usize> = (0..columns_count)
+    .map(|column| {
+      rows
+        .iter()
+        .filter_map(|row| row.get(column))
+        .map(|cell| cell.chars().count())
+        .max()
+        .unwrap_or_default()
+    })
+    .collect();
+  rows
+    .iter()
+    .map(|row| {
+      row
+        .iter()
+        .zip(widths.iter())
+        .map(|(cell, width)| format!("{:width$}", cell, width = width))
+        .collect::<Vec<_>>()
+        .join("  ")
+        .trim_end()
+        .to_string()
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+// Arrays of objects become one row per item with a header, arrays of scalars one row per value and
+// objects one row per key, nested values are written as compact JSON.
+fn render_table(value: &Value) -> String {
+  match value {
+    Value::Array(items) if items.iter().all(Value::is_object) && !items.is_empty() => {
+      let mut columns: Vec<&String> = Vec::new();
+      for item in items.iter().filter_map(Value::as_object) {
+        for key in item.keys() {
+          if !columns.contains(&key) {
+            columns.push(key);
+          }
+        }
+      }
+      let header = columns.iter().map(|column| column.to_uppercase()).collect();
+      let rows = items.iter().map(|item| {
+        columns
+          .iter()
+          .map(|column| render_cell(item.get(column.as_str()).unwrap_or(&Value::Null)))
+          .collect()
+      });
+      render_rows(&std::iter::once(header).chain(rows).collect::<Vec<_>>())
+    }
+    Value::Array(items) => items.iter().map(render_cell).collect::<Vec<_>>().join("\n"),
+    Value::Object(object) => render_rows(
+      &object
+        .iter()
+        .map(|(key, value)| vec![key.clone(), render_cell(value)])
+        .collect::<Vec<_>>(),
+    ),
+    _ => render_cell(value),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn table_from_objects() {
+    assert_eq!(
+      render_table(&json!([
+        {"timestamp": 1464600000, "context": {"value": 3}},
+        {"timestamp": 1464600060, "context": {}, "agent_id": "foo"}
+      ])),
+      "CONTEXT      TIMESTAMP   AGENT_ID\n{\"value\":3}  1464600000\n{}           1464600060  foo"
+    );
+  }
+
+  #[test]
+  fn table_from_scalars_and_object() {
+    assert_eq!(render_table(&json!(["foo", "bar"])), "foo\nbar");
+    assert_eq!(
+      render_table(&json!({"name": "foo", "firstTimestamp": null, "deleted": true})),
+      "deleted         true\nfirstTimestamp\nname            foo"
+    );
+  }
+
+  #[test]
+  fn render_formats() {
+    let content = json!({"name": "foo"});
+    assert_eq!(
+      OutputFormat::Json.render(&content).unwrap().unwrap(),
+      "{\n  \"name\": \"foo\"\n}"
+    );
+    assert_eq!(
+      OutputFormat::Yaml.render(&content).unwrap().unwrap(),
+      "---\nname: foo\n"
+    );
+    assert_eq!(OutputFormat::Quiet.render(&content).unwrap(), None);
+  }
+}
@@ -0,0 +1,147 @@
+use http::header::{HeaderMap, RETRY_AFTER};
+use http::Method;
+use rand::Rng;
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+  pub max_attempts: u32,
+  pub initial_backoff: Duration,
+  // Also caps the delays asked by the platform through `Retry-After`
+  pub max_backoff: Duration,
+  // Retries non idempotent requests (i.e. POST) on server and network errors as well, e.g. for
+  // context uploads where sending the same operations again is harmless
+  pub retry_writes: bool,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> RetryPolicy {
+    RetryPolicy {
+      max_attempts: 3,
+      initial_backoff: Duration::from_millis(500),
+      max_backoff: Duration::from_secs(10),
+      retry_writes: false,
+    }
+  }
+}
+
+impl RetryPolicy {
+  pub fn none() -> RetryPolicy {
+    RetryPolicy {
+      max_attempts: 1,
+      ..RetryPolicy::default()
+    }
+  }
+
+  pub(crate) fn should_retry(&self, attempt: u32) -> bool {
+    attempt < self.max_attempts
+  }
+
+  // A failed request might have been applied, it is only sent again when that's harmless
+  pub(crate) fn retries_failures_of(&self, method: &Method) -> bool {
+    self.retry_writes || method.is_idempotent()
+  }
+
+  // Exponential backoff with "equal jitter": half of the delay is fixed, the other half is random.
+  pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(31);
+    let delay = self
+      .initial_backoff
+      .checked_mul(1 << exponent)
+      .map_or(self.max_backoff, |delay| delay.min(self.max_backoff));
+    let half_delay = delay / 2;
+    half_delay + half_delay.mul_f64(rand::thread_rng().gen::<f64>())
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollingPolicy {
+  pub interval: Duration,
+  pub timeout: Duration,
+}
+
+impl Default for PollingPolicy {
+  fn default() -> PollingPolicy {
+    PollingPolicy {
+      interval: Duration::from_secs(1),
+      timeout: Duration::from_secs(600),
+    }
+  }
+}
+
+// Only the delay-seconds form of `Retry-After` is supported, the platform never sends HTTP dates.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+  headers
+    .get(RETRY_AFTER)?
+    .to_str()
+    .ok()?
+    .trim()
+    .parse::<u64>()
+    .ok()
+    .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn backoff_grows_exponentially() {
+    let retry_policy = RetryPolicy {
+      max_attempts: 10,
+      initial_backoff: Duration::from_millis(100),
+      max_backoff: Duration::from_secs(1),
+      retry_writes: false,
+    };
+    for (attempt, max_delay) in [
+      (1, 100),
+      (2, 200),
+      (3, 400),
+      (4, 800),
+      (5, 1000),
+      (40, 1000),
+    ]
+    .iter()
+    .cloned()
+    {
+      let delay = retry_policy.backoff(attempt);
+      assert!(delay >= Duration::from_millis(max_delay / 2));
+      assert!(delay <= Duration::from_millis(max_delay));
+    }
+  }
+
+  #[test]
+  fn should_retry() {
+    let retry_policy = RetryPolicy::default();
+    assert!(retry_policy.should_retry(1));
+    assert!(retry_policy.should_retry(2));
+    assert!(!retry_policy.should_retry(3));
+    assert!(!RetryPolicy::none().should_retry(1));
+  }
+
+  #[test]
+  fn retries_failures_of() {
+    let retry_policy = RetryPolicy::default();
+    assert!(retry_policy.retries_failures_of(&Method::GET));
+    assert!(retry_policy.retries_failures_of(&Method::DELETE));
+    assert!(!retry_policy.retries_failures_of(&Method::POST));
+    let retry_policy = RetryPolicy {
+      retry_writes: true,
+      ..RetryPolicy::default()
+    };
+    assert!(retry_policy.retries_failures_of(&Method::POST));
+  }
+
+  #[test]
+  fn retry_after_header() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(retry_after(&headers), None);
+    headers.insert(RETRY_AFTER, "12".parse().unwrap());
+    assert_eq!(retry_after(&headers), Some(Duration::from_secs(12)));
+    headers.insert(
+      RETRY_AFTER,
+      "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+    );
+    assert_eq!(retry_after(&headers), None);
+  }
+}
@@ -0,0 +1,170 @@
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+// tokio's semaphore can't hold more permits
+pub(crate) const MAX_IN_FLIGHT: usize = usize::MAX >> 3;
+// One request a day, the wait for a token reserved at slower rates could overflow a `Duration`
+pub(crate) const MIN_REQUESTS_PER_SECOND: f64 = 1. / 86_400.;
+
+// Token bucket limiting the rate of requests sent by a client, retries included
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimit {
+  pub requests_per_second: f64,
+  // Count of requests that can be sent at once after an idle period
+  pub burst: u32,
+}
+
+struct TokenBucket {
+  rate_limit: RateLimit,
+  // Available tokens, negative when requests are waiting for tokens they reserved
+  tokens: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+  fn new(rate_limit: &RateLimit, now: Instant) -> TokenBucket {
+    TokenBucket {
+      rate_limit: rate_limit.clone(),
+      tokens: Mutex::new((f64::from(rate_limit.burst), now)),
+    }
+  }
+
+  // Reserves a token, returns how long to wait before it is available
+  fn reserve(&self, now: Instant) -> Duration {
+    let mut tokens = self.tokens.lock().unwrap_or_else(|err| err.into_inner());
+    let (available_tokens, updated_at) = *tokens;
+    let refilled_tokens =
+      now.saturating_duration_since(updated_at).as_secs_f64() * self.rate_limit.requests_per_second;
+    let available_tokens =
+      (available_tokens + refilled_tokens).min(f64::from(self.rate_limit.burst)) - 1.;
+    *tokens = (available_tokens, now.max(updated_at));
+    if available_tokens >= 0. {
+      Duration::from_secs(0)
+    } else {
+      Duration::from_secs_f64(-available_tokens / self.rate_limit.requests_per_second)
+    }
+  }
+
+  // Gives back a token reserved by a request that won't be sent
+  fn refund(&self) {
+    let mut tokens = self.tokens.lock().unwrap_or_else(|err| err.into_inner());
+    tokens.0 = (tokens.0 + 1.).min(f64::from(self.rate_limit.burst));
+  }
+}
+
+// A reserved token is refunded when dropped before being used, e.g. when the future waiting for it
+// is cancelled
+struct Reservation<'a> {
+  token_bucket: Option<&'a TokenBucket>,
+}
+
+impl Reservation<'_> {
+  fn use_token(mut self) {
+    self.token_bucket = None;
+  }
+}
+
+impl Drop for Reservation<'_> {
+  fn drop(&mut self) {
+    if let Some(token_bucket) = self.token_bucket {
+      token_bucket.refund();
+    }
+  }
+}
+
+// Client-side enforcement of the rate limit and of the maximum count of requests in flight
+pub(crate) struct Throttle {
+  token_bucket: Option<TokenBucket>,
+  semaphore: Option<Semaphore>,
+}
+
+impl Throttle {
+  pub fn new(rate_limit: Option<&RateLimit>, max_in_flight: Option<usize>) -> Throttle {
+    Throttle {
+      token_bucket: rate_limit.map(|rate_limit| TokenBucket::new(rate_limit, Instant::now())),
+      semaphore: max_in_flight.map(Semaphore::new),
+    }
+  }
+
+  // Reserves a token and returns how long to wait for it
+  fn reserve(&self) -> (Reservation<'_>, Duration) {
+    match &self.token_bucket {
+      Some(token_bucket) => (
+        Reservation {
+          token_bucket: Some(token_bucket),
+        },
+        token_bucket.reserve(Instant::now()),
+      ),
+      None => (Reservation { token_bucket: None }, Duration::from_secs(0)),
+    }
+  }
+
+  // Waits for the rate limit, then for a slot among the requests in flight so that slots aren't
+  // held while waiting for tokens. The slot is released when the returned permit is dropped.
+  // Waiting for the semaphore doesn't depend on the runtime, only the sleep differs between clients.
+  pub async fn acquire<SleepT, SleepFutureT>(&self, sleep: SleepT) -> Option<SemaphorePermit<'_>>
+  where
+    SleepT: Fn(Duration) -> SleepFutureT,
+    SleepFutureT: Future<Output = ()>,
+  {
+    let (reservation, delay) = self.reserve();
+    if delay > Duration::from_secs(0) {
+      sleep(delay).await;
+    }
+    let permit = match &self.semaphore {
+      Some(semaphore) => Some(semaphore.acquire().await),
+      None => None,
+    };
+    reservation.use_token();
+    permit
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn token_bucket_reservations() {
+    let now = Instant::now();
+    let token_bucket = TokenBucket::new(
+      &RateLimit {
+        requests_per_second: 10.,
+        burst: 2,
+      },
+      now,
+    );
+    // The burst is available right away, then tokens are spaced by 100ms
+    assert_eq!(token_bucket.reserve(now), Duration::from_secs(0));
+    assert_eq!(token_bucket.reserve(now), Duration::from_secs(0));
+    assert_eq!(token_bucket.reserve(now), Duration::from_millis(100));
+    assert_eq!(token_bucket.reserve(now), Duration::from_millis(200));
+    // After a long idle period only the burst is available
+    let later = now + Duration::from_secs(10);
+    assert_eq!(token_bucket.reserve(later), Duration::from_secs(0));
+    assert_eq!(token_bucket.reserve(later), Duration::from_secs(0));
+    assert_eq!(token_bucket.reserve(later), Duration::from_millis(100));
+  }
+
+  #[test]
+  fn cancelled_reservations_are_refunded() {
+    let throttle = Throttle::new(
+      Some(&RateLimit {
+        requests_per_second: 10.,
+        burst: 1,
+      }),
+      None,
+    );
+    let (reservation, delay) = throttle.reserve();
+    assert_eq!(delay, Duration::from_secs(0));
+    reservation.use_token();
+    let (reservation, delay) = throttle.reserve();
+    assert!(delay > Duration::from_millis(50));
+    // Dropping the reservation gives its token back, the next one waits as long
+    drop(reservation);
+    let (_reservation, next_delay) = throttle.reserve();
+    assert!(next_delay <= delay);
+    assert!(next_delay > Duration::from_millis(50));
+  }
+}
@@ -1,16 +1,24 @@
 use crate::error::Error;
 use serde::Deserialize;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct TokenPayload {
   pub platform: String,
   pub owner: String,
   pub project: String,
+  // Tokens without a right are write-capable
+  pub right: Option<String>,
+  // Timestamps are expressed in seconds since the epoch
+  pub iat: Option<u64>,
+  pub exp: Option<u64>,
+  pub iss: Option<String>,
+  pub jti: Option<String>,
 }
 
 impl TokenPayload {
   pub fn from_token(token: &str) -> Result<TokenPayload, Error> {
-    if token.len() == 0 {
+    if token.is_empty() {
       return Err(Error::BadToken("Empty token".to_string(), None));
     }
     let raw_segments: Vec<&str> = token.split(".").collect();
@@ -25,13 +33,18 @@ impl TokenPayload {
       ));
     }
 
-    let raw_payload_segment = raw_segments[1];
-    let decoded_payload_segment = base64::decode(raw_payload_segment).map_err(|err| {
-      Error::BadToken(
-        "Unable to decode JWT token segment from base64".to_string(),
-        Some(Box::new(err)),
-      )
-    })?;
+    // JWT segments are base64url encoded, padding is optional. Tokens encoded with the standard
+    // alphabet are still accepted.
+    let raw_payload_segment = raw_segments[1].trim_end_matches('=');
+    let decoded_payload_segment =
+      base64::decode_config(raw_payload_segment, base64::URL_SAFE_NO_PAD)
+        .or_else(|_err| base64::decode_config(raw_payload_segment, base64::STANDARD_NO_PAD))
+        .map_err(|err| {
+          Error::BadToken(
+            "Unable to decode JWT token segment from base64".to_string(),
+            Some(Box::new(err)),
+          )
+        })?;
 
     let payload: TokenPayload = serde_json::from_slice(decoded_payload_segment.as_slice())
       .map_err(|err| {
@@ -43,6 +56,20 @@ impl TokenPayload {
 
     Ok(payload)
   }
+
+  pub fn is_expired(&self) -> bool {
+    let now = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map_or(0, |duration| duration.as_secs());
+    matches!(self.exp, Some(exp) if exp <= now)
+  }
+
+  pub fn has_write_right(&self) -> bool {
+    match &self.right {
+      Some(right) => right == "write",
+      None => true,
+    }
+  }
 }
 
 #[cfg(test)]
@@ -108,5 +135,71 @@ mod tests {
     assert_eq!(token_payload.platform, "https://beta.craft.ai");
     assert_eq!(token_payload.owner, "cloderic");
     assert_eq!(token_payload.project, "sandbox");
+    assert_eq!(token_payload.right.as_deref(), Some("write"));
+    assert_eq!(token_payload.iat, Some(1_489_141_707));
+    assert_eq!(token_payload.exp, None);
+    assert_eq!(token_payload.iss.as_deref(), Some("https://beta.craft.ai"));
+    assert_eq!(
+      token_payload.jti.as_deref(),
+      Some("e55d2362-6e90-49a3-9dd0-39e28277e3d6")
+    );
+    assert!(!token_payload.is_expired());
+    assert!(token_payload.has_write_right());
+  }
+
+  #[test]
+  fn from_token_base64url() {
+    let padded_token = "e30.eyJvd25lciI6ImNsb2RlcmljIiwicHJvamVjdCI6InNhbmRib3giLCJyaWdodCI6IndyaXRlIiwicGxhdGZvcm0iOiJodHRwczovL2JldGEuY3JhZnQuYWkiLCJqdGkiOiJ-fn4_Pz8-Pj4ifQ==.sig";
+    let token_payload = TokenPayload::from_token(padded_token).unwrap();
+    assert_eq!(token_payload.jti.as_deref(), Some("~~~???>>>"));
+    let unpadded_token = padded_token.replace("==.", ".");
+    let token_payload = TokenPayload::from_token(&unpadded_token).unwrap();
+    assert_eq!(token_payload.owner, "cloderic");
+    assert_eq!(token_payload.jti.as_deref(), Some("~~~???>>>"));
+  }
+
+  #[test]
+  fn from_token_standard_alphabet() {
+    let token = "e30.eyJvd25lciI6ImNsb2RlcmljIiwicHJvamVjdCI6InNhbmRib3giLCJyaWdodCI6IndyaXRlIiwicGxhdGZvcm0iOiJodHRwczovL2JldGEuY3JhZnQuYWkiLCJqdGkiOiJ+fn4/Pz8+Pj4ifQ==.sig";
+    let token_payload = TokenPayload::from_token(token).unwrap();
+    assert_eq!(token_payload.jti.as_deref(), Some("~~~???>>>"));
+    let unpadded_token = token.replace("==.", ".");
+    let token_payload = TokenPayload::from_token(&unpadded_token).unwrap();
+    assert_eq!(token_payload.owner, "cloderic");
+  }
+
+  #[test]
+  fn from_token_expired_read_only() {
+    let payload = serde_json::json!({
+      "owner": "cloderic",
+      "project": "sandbox",
+      "right": "read",
+      "platform": "https://beta.craft.ai",
+      "iat": 1_489_141_707,
+      "exp": 1_489_145_307,
+    });
+    let token_payload = TokenPayload::from_token(&format!(
+      "e30=.{}.sig",
+      base64::encode_config(&payload.to_string(), base64::URL_SAFE_NO_PAD)
+    ))
+    .unwrap();
+    assert!(token_payload.is_expired());
+    assert!(!token_payload.has_write_right());
+  }
+
+  #[test]
+  fn from_token_without_right() {
+    let payload = serde_json::json!({
+      "owner": "cloderic",
+      "project": "sandbox",
+      "platform": "https://beta.craft.ai",
+    });
+    let token_payload = TokenPayload::from_token(&format!(
+      "e30.{}.sig",
+      base64::encode_config(&payload.to_string(), base64::URL_SAFE_NO_PAD)
+    ))
+    .unwrap();
+    assert_eq!(token_payload.right, None);
+    assert!(token_payload.has_write_right());
   }
 }
@@ -0,0 +1,101 @@
+// Instrumentation of API calls with `tracing`, one span per call. Headers are never recorded, they
+// carry the token.
+
+use crate::error::Error;
+use crate::transport::{HttpRequest, HttpResponse};
+use std::time::Duration;
+use tracing::Span;
+
+// Collections whose items are identified by the path segment following them
+const COLLECTIONS: [&str; 2] = ["agents", "generators"];
+
+// Path of the url relative to the project (e.g. `/agents/{id}/context`), without query string
+fn path_template(url: &str, base_url: &str, owner: &str, project: &str) -> String {
+  let path = url.strip_prefix(base_url).unwrap_or(url);
+  let path = path.split('?').next().unwrap_or_default();
+  let project_prefix = format!("/api/v1/{}/{}", owner, project);
+  let path = match path.strip_prefix(project_prefix.as_str()) {
+    Some(project_path) if project_path.is_empty() || project_path.starts_with('/') => project_path,
+    _ => path,
+  };
+  let mut previous_segment = "";
+  path
+    .split('/')
+    .map(|segment| {
+      let templated_segment = if COLLECTIONS.contains(&previous_segment) {
+        "{id}"
+      } else {
+        segment
+      };
+      previous_segment = segment;
+      templated_segment
+    })
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+pub(crate) fn call_span(request: &HttpRequest, base_url: &str, owner: &str, project: &str) -> Span {
+  tracing::info_span!(
+    "craft_ai.request",
+    method = %request.method,
+    path = %path_template(&request.url, base_url, owner, project),
+    owner,
+    project,
+    status = tracing::field::Empty,
+    retries = tracing::field::Empty,
+    request_size = request.body.as_ref().map_or(0, Vec::len) as u64,
+    response_size = tracing::field::Empty,
+    latency_ms = tracing::field::Empty,
+    error = tracing::field::Empty,
+  )
+}
+
+pub(crate) fn record_outcome(
+  span: &Span,
+  response: &Result<HttpResponse, Error>,
+  attempts: u32,
+  elapsed: Duration,
+) {
+  span.record("retries", attempts - 1);
+  span.record("latency_ms", elapsed.as_millis() as u64);
+  match response {
+    Ok(response) => {
+      span.record("status", response.status.as_u16());
+      span.record("response_size", response.body.len() as u64);
+    }
+    Err(err) => {
+      span.record("error", tracing::field::display(err));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn path_templates() {
+    let template = |url| path_template(url, "https://beta.craft.ai", "owner", "project");
+    assert_eq!(template("https://beta.craft.ai/api/v1"), "/api/v1");
+    assert_eq!(
+      template("https://beta.craft.ai/api/v1/owner/project/agents?start=2"),
+      "/agents"
+    );
+    assert_eq!(
+      template("https://beta.craft.ai/api/v1/owner/project/agents/foo/context/state?t=3"),
+      "/agents/{id}/context/state"
+    );
+    assert_eq!(
+      template("https://beta.craft.ai/api/v1/owner/project/generators/bar/decision/tree"),
+      "/generators/{id}/decision/tree"
+    );
+    assert_eq!(
+      template("https://beta.craft.ai/api/v1/owner/project/bulk/agents"),
+      "/bulk/agents"
+    );
+    assert_eq!(
+      template("https://beta.craft.ai/api/v1/owner/projects/agents/foo"),
+      "/api/v1/owner/projects/agents/{id}"
+    );
+  }
+}
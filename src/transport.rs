@@ -0,0 +1,83 @@
+use crate::client::network_error;
+use crate::error::Error;
+use futures::future::BoxFuture;
+use http::{Method, StatusCode};
+use std::fmt;
+use std::sync::Arc;
+
+pub use http::HeaderMap;
+
+// Everything needed to send a request, authorization and default headers are already set
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+  pub method: Method,
+  pub url: String,
+  pub headers: HeaderMap,
+  pub body: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+  pub status: StatusCode,
+  pub headers: HeaderMap,
+  pub body: Vec<u8>,
+}
+
+// Sends requests on behalf of a `Client`. Failing to get a response should be reported as an
+// `Error::NetworkError`, which is the only kind of error the client retries.
+pub trait Transport: Send + Sync {
+  fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>>;
+}
+
+#[derive(Clone)]
+pub(crate) struct SharedTransport(pub Arc<dyn Transport>);
+
+impl fmt::Debug for SharedTransport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("Transport")
+  }
+}
+
+// Default transport of `Client`
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+  http_client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+  pub fn new(http_client: reqwest::Client) -> ReqwestTransport {
+    ReqwestTransport { http_client }
+  }
+}
+
+impl Transport for ReqwestTransport {
+  fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>> {
+    Box::pin(async move {
+      let HttpRequest {
+        method,
+        url,
+        headers,
+        body,
+      } = request;
+      let mut request_builder = self.http_client.request(method, &url).headers(headers);
+      if let Some(body) = body {
+        request_builder = request_builder.body(body);
+      }
+      let response = request_builder
+        .send()
+        .await
+        .map_err(|err| network_error(&url, err))?;
+      let status = response.status();
+      let headers = response.headers().clone();
+      let body = response
+        .bytes()
+        .await
+        .map_err(|err| network_error(&url, err))?;
+      Ok(HttpResponse {
+        status,
+        headers,
+        body: body.to_vec(),
+      })
+    })
+  }
+}
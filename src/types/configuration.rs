@@ -46,6 +46,28 @@ pub struct AgentConfiguration {
   pub forgetting_timestep: Option<u64>,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeneratorConfiguration {
+  // Context configuration
+  pub context: BTreeMap<String, PropertyConfiguration>,
+  pub output: [String; 1],
+  // Agents filter
+  pub filter: Vec<String>,
+  // Generation configuration
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub time_quantum: Option<u64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub learning_period: Option<u64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tree_max_depth: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub min_samples_per_leaf: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tree_max_operations: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub operations_as_events: Option<bool>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ConfigurationBuilder {
   // Context configuration
@@ -67,6 +89,12 @@ pub struct ConfigurationBuilder {
   forgetting_timestep: Option<u64>,
 }
 
+impl Default for ConfigurationBuilder {
+  fn default() -> ConfigurationBuilder {
+    ConfigurationBuilder::new()
+  }
+}
+
 impl ConfigurationBuilder {
   pub fn new() -> ConfigurationBuilder {
     ConfigurationBuilder {
@@ -87,17 +115,17 @@ impl ConfigurationBuilder {
     }
   }
 
-  pub fn add_property<'a, T: Into<String>>(
-    &'a mut self,
+  pub fn add_property<T: Into<String>>(
+    &mut self,
     property_name: T,
     property_type: PropertyType,
     property_is_generated: Option<bool>,
     property_is_optional: Option<bool>,
-  ) -> &'a mut ConfigurationBuilder {
+  ) -> &mut ConfigurationBuilder {
     self.context.insert(
       property_name.into(),
       PropertyConfiguration {
-        property_type: property_type,
+        property_type,
         is_generated: property_is_generated,
         is_optional: property_is_optional,
       },
@@ -105,28 +133,173 @@ impl ConfigurationBuilder {
     self
   }
 
-  pub fn set_output_property<'a, T: Into<String>>(
-    &'a mut self,
+  pub fn set_output_property<T: Into<String>>(
+    &mut self,
     property_name: T,
-  ) -> &'a mut ConfigurationBuilder {
+  ) -> &mut ConfigurationBuilder {
     self.output = Some(property_name.into());
     self
   }
 
-  pub fn set_learning_period<'a>(
-    &'a mut self,
-    learning_period: u64,
-  ) -> &'a mut ConfigurationBuilder {
+  pub fn set_learning_period(&mut self, learning_period: u64) -> &mut ConfigurationBuilder {
     self.learning_period = Some(learning_period);
     self
   }
 
+  pub fn set_time_quantum(&mut self, time_quantum: u64) -> &mut ConfigurationBuilder {
+    self.time_quantum = Some(time_quantum);
+    self
+  }
+
+  pub fn set_tree_max_depth(&mut self, tree_max_depth: usize) -> &mut ConfigurationBuilder {
+    self.tree_max_depth = Some(tree_max_depth);
+    self
+  }
+
+  pub fn set_min_samples_per_leaf(
+    &mut self,
+    min_samples_per_leaf: usize,
+  ) -> &mut ConfigurationBuilder {
+    self.min_samples_per_leaf = Some(min_samples_per_leaf);
+    self
+  }
+
+  pub fn set_tree_max_operations(
+    &mut self,
+    tree_max_operations: usize,
+  ) -> &mut ConfigurationBuilder {
+    self.tree_max_operations = Some(tree_max_operations);
+    self
+  }
+
+  pub fn set_operations_as_events(
+    &mut self,
+    operations_as_events: bool,
+  ) -> &mut ConfigurationBuilder {
+    self.operations_as_events = Some(operations_as_events);
+    self
+  }
+
+  pub fn set_deactivate_covariance_split(
+    &mut self,
+    deactivate_covariance_split: bool,
+  ) -> &mut ConfigurationBuilder {
+    self.deactivate_covariance_split = Some(deactivate_covariance_split);
+    self
+  }
+
+  pub fn set_deactivate_forgetting(
+    &mut self,
+    deactivate_forgetting: bool,
+  ) -> &mut ConfigurationBuilder {
+    self.deactivate_forgetting = Some(deactivate_forgetting);
+    self
+  }
+
+  pub fn set_deactivate_pruning(&mut self, deactivate_pruning: bool) -> &mut ConfigurationBuilder {
+    self.deactivate_pruning = Some(deactivate_pruning);
+    self
+  }
+
+  pub fn set_period_detection(&mut self, period_detection: bool) -> &mut ConfigurationBuilder {
+    self.period_detection = Some(period_detection);
+    self
+  }
+
+  pub fn set_forgetting_similarity_forgetting_ratio(
+    &mut self,
+    forgetting_similarity_forgetting_ratio: f64,
+  ) -> &mut ConfigurationBuilder {
+    self.forgetting_similarity_forgetting_ratio = Some(forgetting_similarity_forgetting_ratio);
+    self
+  }
+
+  pub fn set_forgetting_timestep(&mut self, forgetting_timestep: u64) -> &mut ConfigurationBuilder {
+    self.forgetting_timestep = Some(forgetting_timestep);
+    self
+  }
+
+  fn check(&self, output: &str) -> Result<(), Error> {
+    match self.context.get(output) {
+      None => {
+        return Err(Error::InvalidArgument(format!(
+          "Output property '{}' is not defined in the context",
+          output
+        )))
+      }
+      Some(property) if property.is_generated == Some(true) => {
+        return Err(Error::InvalidArgument(format!(
+          "Output property '{}' can't be a generated property",
+          output
+        )))
+      }
+      _ => {}
+    }
+    let positive_parameters = [
+      ("time_quantum", self.time_quantum),
+      ("learning_period", self.learning_period),
+      (
+        "tree_max_depth",
+        self.tree_max_depth.map(|value| value as u64),
+      ),
+      (
+        "min_samples_per_leaf",
+        self.min_samples_per_leaf.map(|value| value as u64),
+      ),
+      (
+        "tree_max_operations",
+        self.tree_max_operations.map(|value| value as u64),
+      ),
+      ("forgetting_timestep", self.forgetting_timestep),
+    ];
+    for (parameter_name, value) in positive_parameters.iter() {
+      if *value == Some(0) {
+        return Err(Error::InvalidArgument(format!(
+          "'{}' must be strictly positive",
+          parameter_name
+        )));
+      }
+    }
+    if let Some(ratio) = self.forgetting_similarity_forgetting_ratio {
+      if !(ratio.is_finite() && ratio > 0.) {
+        return Err(Error::InvalidArgument(format!(
+          "'forgetting_similarity_forgetting_ratio' must be strictly positive, got {}",
+          ratio
+        )));
+      }
+    }
+    if let (Some(learning_period), Some(time_quantum)) = (self.learning_period, self.time_quantum) {
+      if learning_period < time_quantum {
+        return Err(Error::InvalidArgument(format!(
+          "'learning_period' ({}) must be greater than or equal to 'time_quantum' ({})",
+          learning_period, time_quantum
+        )));
+      }
+    }
+    if self.operations_as_events == Some(true) {
+      if self.learning_period.is_none() {
+        return Err(Error::InvalidArgument(
+          "'learning_period' must be set when 'operations_as_events' is true".to_string(),
+        ));
+      }
+      if self.tree_max_operations.is_none() {
+        return Err(Error::InvalidArgument(
+          "'tree_max_operations' must be set when 'operations_as_events' is true".to_string(),
+        ));
+      }
+    }
+    Ok(())
+  }
+
   pub fn create_agent_configuration(&self) -> Result<AgentConfiguration, Error> {
+    let output = self
+      .output
+      .clone()
+      .ok_or_else(|| Error::InvalidArgument("No defined output property".to_string()))?;
+    self.check(&output)?;
     Ok(AgentConfiguration {
       context: self.context.clone(),
-      output: [self.output.clone().ok_or(Error::InvalidArgument(
-        "No defined output property".to_string(),
-      ))?],
+      output: [output],
       learning_period: self.learning_period,
       time_quantum: self.time_quantum,
       tree_max_depth: self.tree_max_depth,
@@ -141,6 +314,24 @@ impl ConfigurationBuilder {
       forgetting_timestep: self.forgetting_timestep,
     })
   }
+
+  pub fn create_generator_configuration<T: Into<String>>(
+    &self,
+    filter: Vec<T>,
+  ) -> Result<GeneratorConfiguration, Error> {
+    let agent_configuration = self.create_agent_configuration()?;
+    Ok(GeneratorConfiguration {
+      context: agent_configuration.context,
+      output: agent_configuration.output,
+      filter: filter.into_iter().map(Into::into).collect(),
+      time_quantum: agent_configuration.time_quantum,
+      learning_period: agent_configuration.learning_period,
+      tree_max_depth: agent_configuration.tree_max_depth,
+      min_samples_per_leaf: agent_configuration.min_samples_per_leaf,
+      tree_max_operations: agent_configuration.tree_max_operations,
+      operations_as_events: agent_configuration.operations_as_events,
+    })
+  }
 }
 
 #[cfg(test)]
@@ -251,4 +442,123 @@ mod tests {
       .unwrap()
     );
   }
+
+  #[test]
+  fn generator_configuration_to_json_string() {
+    assert_eq!(
+      serde_json::to_string(&ConfigurationBuilder::new()
+      .add_property("day", PropertyType::DayOfWeek, None, None)
+      .add_property("value", PropertyType::Continuous, None, None)
+      .set_output_property("value")
+      .set_learning_period(1500)
+      .create_generator_configuration(vec!["agent-1", "agent-2"])
+      .unwrap())
+      .unwrap(),
+      "{\"context\":{\"day\":{\"type\":\"day_of_week\"},\"value\":{\"type\":\"continuous\"}},\"output\":[\"value\"],\"filter\":[\"agent-1\",\"agent-2\"],\"learning_period\":1500}"
+    );
+  }
+
+  fn configuration_builder() -> ConfigurationBuilder {
+    let mut builder = ConfigurationBuilder::new();
+    builder
+      .add_property("time", PropertyType::TimeOfDay, Some(true), None)
+      .add_property("value", PropertyType::Continuous, None, None)
+      .set_output_property("value");
+    builder
+  }
+
+  #[test]
+  fn agent_configuration_all_parameters() {
+    let configuration = configuration_builder()
+      .set_time_quantum(100)
+      .set_learning_period(1500)
+      .set_tree_max_depth(6)
+      .set_min_samples_per_leaf(4)
+      .set_tree_max_operations(5000)
+      .set_operations_as_events(true)
+      .set_deactivate_covariance_split(true)
+      .set_deactivate_forgetting(false)
+      .set_deactivate_pruning(true)
+      .set_period_detection(false)
+      .set_forgetting_similarity_forgetting_ratio(0.5)
+      .set_forgetting_timestep(60)
+      .create_agent_configuration()
+      .unwrap();
+    assert_eq!(configuration.time_quantum, Some(100));
+    assert_eq!(configuration.tree_max_depth, Some(6));
+    assert_eq!(configuration.min_samples_per_leaf, Some(4));
+    assert_eq!(configuration.operations_as_events, Some(true));
+    assert_eq!(configuration.deactivate_pruning, Some(true));
+    assert_eq!(
+      configuration.forgetting_similarity_forgetting_ratio,
+      Some(0.5)
+    );
+    assert_eq!(configuration.forgetting_timestep, Some(60));
+  }
+
+  #[test]
+  fn agent_configuration_invalid_output() {
+    let error = configuration_builder()
+      .set_output_property("foo")
+      .create_agent_configuration()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Output property 'foo' is not defined in the context"
+    );
+    let error = configuration_builder()
+      .set_output_property("time")
+      .create_agent_configuration()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Output property 'time' can't be a generated property"
+    );
+  }
+
+  #[test]
+  fn agent_configuration_invalid_parameters() {
+    let error = configuration_builder()
+      .set_tree_max_depth(0)
+      .create_agent_configuration()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - 'tree_max_depth' must be strictly positive"
+    );
+    let error = configuration_builder()
+      .set_forgetting_similarity_forgetting_ratio(-1.)
+      .create_agent_configuration()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - 'forgetting_similarity_forgetting_ratio' must be strictly positive, got -1"
+    );
+    let error = configuration_builder()
+      .set_time_quantum(100)
+      .set_learning_period(50)
+      .create_agent_configuration()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - 'learning_period' (50) must be greater than or equal to 'time_quantum' (100)"
+    );
+    let error = configuration_builder()
+      .set_operations_as_events(true)
+      .set_learning_period(1500)
+      .create_agent_configuration()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - 'tree_max_operations' must be set when 'operations_as_events' is true"
+    );
+    let error = configuration_builder()
+      .set_operations_as_events(true)
+      .create_agent_configuration()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - 'learning_period' must be set when 'operations_as_events' is true"
+    );
+  }
 }
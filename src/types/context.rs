@@ -0,0 +1,463 @@
+use crate::error::Error;
+use crate::types::configuration::{AgentConfiguration, PropertyConfiguration};
+use crate::types::property::PropertyType;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+pub type Context = BTreeMap<String, Value>;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContextOperation {
+  pub timestamp: u64,
+  pub context: Context,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeneratorContextOperation {
+  #[serde(rename = "agent_id")]
+  pub agent_name: String,
+  pub timestamp: u64,
+  pub context: Context,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContextValue {
+  Continuous(f64),
+  Enum(String),
+  Boolean(bool),
+  Timezone(String),
+  Periodic(u32),
+  // Unknown value of an optional property
+  Null,
+  // Optional property that doesn't apply, sent as `{}`
+  Deactivated,
+}
+
+impl From<ContextValue> for Value {
+  fn from(value: ContextValue) -> Value {
+    match value {
+      ContextValue::Continuous(number) => Value::from(number),
+      ContextValue::Enum(string) | ContextValue::Timezone(string) => Value::String(string),
+      ContextValue::Boolean(boolean) => Value::Bool(boolean),
+      ContextValue::Periodic(number) => Value::from(number),
+      ContextValue::Null => Value::Null,
+      ContextValue::Deactivated => Value::Object(Default::default()),
+    }
+  }
+}
+
+impl Serialize for ContextValue {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    Value::from(self.clone()).serialize(serializer)
+  }
+}
+
+impl ContextValue {
+  fn matches_type(&self, property_type: &PropertyType) -> bool {
+    match self {
+      ContextValue::Continuous(_) => matches!(
+        property_type,
+        PropertyType::Continuous | PropertyType::TimeOfDay | PropertyType::Periodic
+      ),
+      ContextValue::Periodic(_) => matches!(
+        property_type,
+        PropertyType::Periodic
+          | PropertyType::TimeOfDay
+          | PropertyType::DayOfWeek
+          | PropertyType::DayOfMonth
+          | PropertyType::MonthOfYear
+      ),
+      ContextValue::Enum(_) => *property_type == PropertyType::Enum,
+      ContextValue::Boolean(_) => *property_type == PropertyType::Boolean,
+      ContextValue::Timezone(_) => *property_type == PropertyType::Timezone,
+      ContextValue::Null | ContextValue::Deactivated => true,
+    }
+  }
+}
+
+impl ContextValue {
+  fn from_value(property_type: &PropertyType, value: &Value) -> Option<ContextValue> {
+    match (property_type, value) {
+      (_, Value::Null) => Some(ContextValue::Null),
+      (_, Value::Object(object)) if object.is_empty() => Some(ContextValue::Deactivated),
+      (PropertyType::Continuous, _) | (PropertyType::TimeOfDay, _) => {
+        value.as_f64().map(ContextValue::Continuous)
+      }
+      (PropertyType::DayOfWeek, _)
+      | (PropertyType::DayOfMonth, _)
+      | (PropertyType::MonthOfYear, _)
+      | (PropertyType::Periodic, _) => match value.as_u64() {
+        Some(number) if number <= u64::from(u32::MAX) => {
+          Some(ContextValue::Periodic(number as u32))
+        }
+        _ => value.as_f64().map(ContextValue::Continuous),
+      },
+      (PropertyType::Enum, Value::String(string)) => Some(ContextValue::Enum(string.clone())),
+      (PropertyType::Timezone, Value::String(string)) => {
+        Some(ContextValue::Timezone(string.clone()))
+      }
+      (PropertyType::Boolean, Value::Bool(boolean)) => Some(ContextValue::Boolean(*boolean)),
+      _ => None,
+    }
+  }
+}
+
+// Full context of an agent at a given timestamp, with values typed after its configuration
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ContextState {
+  pub timestamp: u64,
+  pub context: BTreeMap<String, ContextValue>,
+}
+
+impl ContextState {
+  pub(crate) fn from_context(
+    timestamp: u64,
+    context: &Context,
+    configuration: &AgentConfiguration,
+  ) -> Result<ContextState, Error> {
+    let context = context
+      .iter()
+      .map(|(property_name, value)| {
+        let property = configuration.context.get(property_name).ok_or_else(|| {
+          Error::InternalError(
+            format!("Unknown property '{}' in context state", property_name),
+            None,
+          )
+        })?;
+        let context_value =
+          ContextValue::from_value(&property.property_type, value).ok_or_else(|| {
+            Error::InternalError(
+              format!(
+                "Unexpected value '{}' for property '{}' of type '{}' in context state",
+                value, property_name, property.property_type
+              ),
+              None,
+            )
+          })?;
+        Ok((property_name.clone(), context_value))
+      })
+      .collect::<Result<_, Error>>()?;
+    Ok(ContextState { timestamp, context })
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct ContextBuilder<'a> {
+  configuration: &'a AgentConfiguration,
+  values: BTreeMap<String, ContextValue>,
+}
+
+impl<'a> ContextBuilder<'a> {
+  pub fn new(configuration: &'a AgentConfiguration) -> ContextBuilder<'a> {
+    ContextBuilder {
+      configuration,
+      values: BTreeMap::new(),
+    }
+  }
+
+  pub fn add_value<T: Into<String>>(
+    &mut self,
+    property_name: T,
+    value: ContextValue,
+  ) -> &mut ContextBuilder<'a> {
+    self.values.insert(property_name.into(), value);
+    self
+  }
+
+  pub fn create_context(&self) -> Result<Context, Error> {
+    self
+      .values
+      .iter()
+      .map(|(property_name, value)| {
+        let property = self
+          .configuration
+          .context
+          .get(property_name)
+          .ok_or_else(|| {
+            Error::InvalidArgument(format!("Unknown property '{}' in context", property_name))
+          })?;
+        if property.is_generated == Some(true) {
+          return Err(Error::InvalidArgument(format!(
+            "Property '{}' is generated, its value can't be set",
+            property_name
+          )));
+        }
+        // JSON has no NaN nor infinity, they would be sent as `null`
+        if let ContextValue::Continuous(number) = value {
+          if !number.is_finite() {
+            return Err(Error::InvalidArgument(format!(
+              "Invalid value '{}' for property '{}' of type '{}', numbers must be finite",
+              number, property_name, property.property_type
+            )));
+          }
+        }
+        let json_value = Value::from(value.clone());
+        if !value.matches_type(&property.property_type)
+          || !check_context_value(property, &json_value)
+        {
+          return Err(Error::InvalidArgument(format!(
+            "Invalid value '{}' for property '{}' of type '{}'",
+            json_value, property_name, property.property_type
+          )));
+        }
+        Ok((property_name.clone(), json_value))
+      })
+      .collect()
+  }
+
+  pub fn create_context_operation(&self, timestamp: u64) -> Result<ContextOperation, Error> {
+    Ok(ContextOperation::new(timestamp, self.create_context()?))
+  }
+}
+
+// `null` and `{}` are only accepted for optional properties
+pub(crate) fn check_context_value(property: &PropertyConfiguration, value: &Value) -> bool {
+  match value {
+    Value::Null => property.is_optional.unwrap_or(false),
+    Value::Object(object) if object.is_empty() => property.is_optional.unwrap_or(false),
+    _ => check_property_value(&property.property_type, value),
+  }
+}
+
+pub(crate) fn check_property_value(property_type: &PropertyType, value: &Value) -> bool {
+  match property_type {
+    PropertyType::Boolean => value.is_boolean(),
+    PropertyType::Continuous => value.is_number(),
+    PropertyType::Enum | PropertyType::Timezone => value.is_string(),
+    PropertyType::TimeOfDay => matches!(value.as_f64(), Some(time) if (0. ..24.).contains(&time)),
+    PropertyType::DayOfWeek => matches!(value.as_u64(), Some(day) if day <= 6),
+    PropertyType::DayOfMonth => matches!(value.as_u64(), Some(day) if (1..=31).contains(&day)),
+    PropertyType::MonthOfYear => matches!(value.as_u64(), Some(month) if (1..=12).contains(&month)),
+    PropertyType::Periodic => value.is_number(),
+  }
+}
+
+impl ContextOperation {
+  pub fn new(timestamp: u64, context: Context) -> ContextOperation {
+    ContextOperation { timestamp, context }
+  }
+
+  pub fn check(&self, configuration: &AgentConfiguration) -> Result<(), Error> {
+    for (property_name, value) in self.context.iter() {
+      let property = configuration.context.get(property_name).ok_or_else(|| {
+        Error::InvalidArgument(format!(
+          "Unknown property '{}' in context operation at timestamp {}",
+          property_name, self.timestamp
+        ))
+      })?;
+      if !check_context_value(property, value) {
+        return Err(Error::InvalidArgument(format!(
+          "Invalid value '{}' for property '{}' of type '{}' in context operation at timestamp {}",
+          value, property_name, property.property_type, self.timestamp
+        )));
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::types::configuration::ConfigurationBuilder;
+  use serde_json::json;
+
+  fn configuration() -> AgentConfiguration {
+    ConfigurationBuilder::new()
+      .add_property("time", PropertyType::TimeOfDay, None, None)
+      .add_property("tz", PropertyType::Timezone, None, None)
+      .add_property("presence", PropertyType::Enum, None, Some(true))
+      .add_property("value", PropertyType::Continuous, None, None)
+      .set_output_property("value")
+      .create_agent_configuration()
+      .unwrap()
+  }
+
+  fn context(value: Value) -> Context {
+    serde_json::from_value(value).unwrap()
+  }
+
+  #[test]
+  fn context_operation_to_json_string() {
+    assert_eq!(
+      serde_json::to_string(&ContextOperation::new(
+        1_464_600_000,
+        context(json!({"tz": "+02:00", "value": 3.5}))
+      ))
+      .unwrap(),
+      "{\"timestamp\":1464600000,\"context\":{\"tz\":\"+02:00\",\"value\":3.5}}"
+    );
+  }
+
+  #[test]
+  fn context_operation_check_valid() {
+    ContextOperation::new(
+      1_464_600_000,
+      context(json!({"tz": "+02:00", "presence": null, "value": 3.5, "time": 12.5})),
+    )
+    .check(&configuration())
+    .unwrap();
+  }
+
+  #[test]
+  fn context_operation_check_unknown_property() {
+    let error = ContextOperation::new(1_464_600_000, context(json!({"foo": 3})))
+      .check(&configuration())
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Unknown property 'foo' in context operation at timestamp 1464600000"
+    );
+  }
+
+  #[test]
+  fn context_operation_check_invalid_value() {
+    let error = ContextOperation::new(1_464_600_000, context(json!({"value": "foo"})))
+      .check(&configuration())
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Invalid value '\"foo\"' for property 'value' of type 'continuous' in context operation at timestamp 1464600000"
+    );
+    let error = ContextOperation::new(1_464_600_000, context(json!({"value": null})))
+      .check(&configuration())
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Invalid value 'null' for property 'value' of type 'continuous' in context operation at timestamp 1464600000"
+    );
+  }
+
+  #[test]
+  fn context_builder_valid() {
+    let configuration = configuration();
+    let context_operation = ContextBuilder::new(&configuration)
+      .add_value("time", ContextValue::Continuous(12.5))
+      .add_value("tz", ContextValue::Timezone("+02:00".to_string()))
+      .add_value("presence", ContextValue::Deactivated)
+      .add_value("value", ContextValue::Continuous(3.5))
+      .create_context_operation(1_464_600_000)
+      .unwrap();
+    assert_eq!(
+      context_operation.context,
+      context(json!({"time": 12.5, "tz": "+02:00", "presence": {}, "value": 3.5}))
+    );
+    context_operation.check(&configuration).unwrap();
+    let context = ContextBuilder::new(&configuration)
+      .add_value("presence", ContextValue::Null)
+      .add_value("time", ContextValue::Periodic(7))
+      .create_context()
+      .unwrap();
+    assert_eq!(context["presence"], Value::Null);
+    assert_eq!(context["time"], json!(7));
+  }
+
+  #[test]
+  fn context_builder_invalid_values() {
+    let configuration = configuration();
+    let error = ContextBuilder::new(&configuration)
+      .add_value("value", ContextValue::Enum("foo".to_string()))
+      .create_context()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Invalid value '\"foo\"' for property 'value' of type 'continuous'"
+    );
+    let error = ContextBuilder::new(&configuration)
+      .add_value("time", ContextValue::Continuous(25.))
+      .create_context()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Invalid value '25.0' for property 'time' of type 'time_of_day'"
+    );
+    let error = ContextBuilder::new(&configuration)
+      .add_value("value", ContextValue::Null)
+      .create_context()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Invalid value 'null' for property 'value' of type 'continuous'"
+    );
+    let error = ContextBuilder::new(&configuration)
+      .add_value("value", ContextValue::Continuous(f64::NAN))
+      .create_context()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Invalid value 'NaN' for property 'value' of type 'continuous', numbers must be finite"
+    );
+    // Even for optional properties, for which `null` is valid
+    let configuration = ConfigurationBuilder::new()
+      .add_property("value", PropertyType::Continuous, None, Some(true))
+      .set_output_property("value")
+      .create_agent_configuration()
+      .unwrap();
+    let error = ContextBuilder::new(&configuration)
+      .add_value("value", ContextValue::Continuous(f64::INFINITY))
+      .create_context()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Invalid value 'inf' for property 'value' of type 'continuous', numbers must be finite"
+    );
+    let error = ContextBuilder::new(&configuration)
+      .add_value("foo", ContextValue::Boolean(true))
+      .create_context()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Unknown property 'foo' in context"
+    );
+  }
+
+  #[test]
+  fn context_state_from_context() {
+    let context_state = ContextState::from_context(
+      1_464_600_000,
+      &context(json!({"time": 12.5, "tz": "+02:00", "presence": {}, "value": 3})),
+      &configuration(),
+    )
+    .unwrap();
+    assert_eq!(context_state.timestamp, 1_464_600_000);
+    assert_eq!(
+      context_state.context["time"],
+      ContextValue::Continuous(12.5)
+    );
+    assert_eq!(
+      context_state.context["tz"],
+      ContextValue::Timezone("+02:00".to_string())
+    );
+    assert_eq!(context_state.context["presence"], ContextValue::Deactivated);
+    assert_eq!(context_state.context["value"], ContextValue::Continuous(3.));
+    let error = ContextState::from_context(
+      1_464_600_000,
+      &context(json!({"value": "foo"})),
+      &configuration(),
+    )
+    .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Internal error - Unexpected value '\"foo\"' for property 'value' of type 'continuous' in context state"
+    );
+  }
+
+  #[test]
+  fn context_builder_generated_property() {
+    let configuration = ConfigurationBuilder::new()
+      .add_property("day", PropertyType::DayOfWeek, Some(true), None)
+      .add_property("value", PropertyType::Continuous, None, None)
+      .set_output_property("value")
+      .create_agent_configuration()
+      .unwrap();
+    let error = ContextBuilder::new(&configuration)
+      .add_value("day", ContextValue::Periodic(3))
+      .create_context()
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Property 'day' is generated, its value can't be set"
+    );
+  }
+}
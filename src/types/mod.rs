@@ -1,5 +1,19 @@
 mod configuration;
+mod context;
 mod property;
+mod time;
+mod tree;
 
-pub use configuration::{AgentConfiguration, ConfigurationBuilder};
+pub use configuration::{
+  AgentConfiguration, ConfigurationBuilder, GeneratorConfiguration, PropertyConfiguration,
+};
+pub(crate) use context::check_context_value;
+pub use context::{
+  Context, ContextBuilder, ContextOperation, ContextState, ContextValue, GeneratorContextOperation,
+};
 pub use property::PropertyType;
+pub use time::Time;
+pub use tree::{
+  DecisionNode, DecisionRule, DecisionTree, DecisionTreeVersion, Distribution, LeafNode, Operator,
+  Prediction, TreeNode,
+};
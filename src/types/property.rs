@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -14,6 +15,23 @@ pub enum PropertyType {
   Enum,
 }
 
+impl fmt::Display for PropertyType {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let name = match self {
+      PropertyType::Boolean => "boolean",
+      PropertyType::TimeOfDay => "time_of_day",
+      PropertyType::DayOfMonth => "day_of_month",
+      PropertyType::DayOfWeek => "day_of_week",
+      PropertyType::MonthOfYear => "month_of_year",
+      PropertyType::Timezone => "timezone",
+      PropertyType::Continuous => "continuous",
+      PropertyType::Periodic => "periodic",
+      PropertyType::Enum => "enum",
+    };
+    write!(f, "{}", name)
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -0,0 +1,258 @@
+use crate::error::Error;
+use crate::types::configuration::AgentConfiguration;
+use crate::types::context::Context;
+use crate::types::property::PropertyType;
+#[cfg(feature = "tz")]
+use chrono::Offset;
+use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Timelike, Utc};
+#[cfg(feature = "tz")]
+use chrono_tz::Tz;
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Time {
+  date_time: DateTime<FixedOffset>,
+}
+
+// Parses offsets such as "+02:00", "-0530" or "+02", returns `None` for anything else
+fn parse_offset(timezone: &str) -> Option<FixedOffset> {
+  if timezone == "Z" {
+    return FixedOffset::east_opt(0);
+  }
+  let (sign, offset) = match timezone.chars().next()? {
+    '+' => (1, &timezone[1..]),
+    '-' => (-1, &timezone[1..]),
+    _ => return None,
+  };
+  let digits = offset.replacen(':', "", 1);
+  if !digits.chars().all(|c| c.is_ascii_digit()) {
+    return None;
+  }
+  let (hours, minutes) = match digits.len() {
+    2 => (digits.parse::<i32>().ok()?, 0),
+    4 => (
+      digits[..2].parse::<i32>().ok()?,
+      digits[2..].parse::<i32>().ok()?,
+    ),
+    _ => return None,
+  };
+  if hours > 14 || minutes >= 60 {
+    return None;
+  }
+  FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+impl Time {
+  // `timezone` is either an offset (e.g. "+02:00") or, with the `tz` feature, an IANA
+  // timezone name (e.g. "Europe/Paris")
+  pub fn new(timestamp: u64, timezone: &str) -> Result<Time, Error> {
+    let utc_date_time = Utc
+      .timestamp_opt(timestamp as i64, 0)
+      .single()
+      .ok_or_else(|| Error::InvalidArgument(format!("Invalid timestamp {}", timestamp)))?;
+    let invalid_timezone = || Error::InvalidArgument(format!("Invalid timezone '{}'", timezone));
+    let offset = match parse_offset(timezone) {
+      Some(offset) => offset,
+      #[cfg(feature = "tz")]
+      None => timezone
+        .parse::<Tz>()
+        .map_err(|_err| invalid_timezone())?
+        .offset_from_utc_datetime(&utc_date_time.naive_utc())
+        .fix(),
+      #[cfg(not(feature = "tz"))]
+      None => return Err(invalid_timezone()),
+    };
+    Ok(Time {
+      date_time: utc_date_time.with_timezone(&offset),
+    })
+  }
+
+  pub fn timestamp(&self) -> u64 {
+    self.date_time.timestamp() as u64
+  }
+
+  pub fn time_of_day(&self) -> f64 {
+    f64::from(self.date_time.hour())
+      + f64::from(self.date_time.minute()) / 60.
+      + f64::from(self.date_time.second()) / 3600.
+  }
+
+  // Monday is 0, Sunday is 6
+  pub fn day_of_week(&self) -> u32 {
+    self.date_time.weekday().num_days_from_monday()
+  }
+
+  pub fn day_of_month(&self) -> u32 {
+    self.date_time.day()
+  }
+
+  pub fn month_of_year(&self) -> u32 {
+    self.date_time.month()
+  }
+
+  pub fn timezone(&self) -> String {
+    let offset = self.date_time.offset().local_minus_utc();
+    format!(
+      "{}{:02}:{:02}",
+      if offset < 0 { '-' } else { '+' },
+      offset.abs() / 3600,
+      offset.abs() % 3600 / 60
+    )
+  }
+
+  pub fn generated_value(&self, property_type: &PropertyType) -> Option<Value> {
+    match property_type {
+      PropertyType::TimeOfDay => Some(Value::from(self.time_of_day())),
+      PropertyType::DayOfWeek => Some(Value::from(self.day_of_week())),
+      PropertyType::DayOfMonth => Some(Value::from(self.day_of_month())),
+      PropertyType::MonthOfYear => Some(Value::from(self.month_of_year())),
+      PropertyType::Timezone => Some(Value::from(self.timezone())),
+      _ => None,
+    }
+  }
+
+  // Time properties are generated unless `is_generated` is explicitly false, timezone properties
+  // are only filled when the context doesn't provide them. A timezone provided by the context takes
+  // precedence over the one of `self` to generate the values.
+  pub fn fill_context(
+    &self,
+    configuration: &AgentConfiguration,
+    context: &mut Context,
+  ) -> Result<(), Error> {
+    let context_timezone = configuration
+      .context
+      .iter()
+      .filter(|(_, property)| property.property_type == PropertyType::Timezone)
+      .find_map(|(property_name, _)| context.get(property_name).and_then(Value::as_str));
+    let time = match context_timezone {
+      Some(timezone) => Time::new(self.timestamp(), timezone)?,
+      None => self.clone(),
+    };
+    for (property_name, property) in configuration.context.iter() {
+      let is_filled = match property.property_type {
+        PropertyType::Timezone => !context.contains_key(property_name),
+        _ => property.is_generated != Some(false),
+      };
+      if !is_filled {
+        continue;
+      }
+      if let Some(value) = time.generated_value(&property.property_type) {
+        context.insert(property_name.clone(), value);
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  #[cfg(feature = "tz")]
+  use crate::types::configuration::ConfigurationBuilder;
+  #[cfg(feature = "tz")]
+  use serde_json::json;
+
+  #[test]
+  fn time_from_offset() {
+    let time = Time::new(1_464_600_000, "-05:30").unwrap();
+    assert_eq!(time.timestamp(), 1_464_600_000);
+    assert!((time.time_of_day() - 3.833_333).abs() < 1e-6);
+    assert_eq!(time.day_of_week(), 0);
+    assert_eq!(time.day_of_month(), 30);
+    assert_eq!(time.month_of_year(), 5);
+    assert_eq!(time.timezone(), "-05:30");
+    assert_eq!(
+      Time::new(1_464_600_000, "+0200").unwrap().timezone(),
+      "+02:00"
+    );
+    assert_eq!(Time::new(1_464_600_000, "Z").unwrap().timezone(), "+00:00");
+  }
+
+  #[cfg(feature = "tz")]
+  #[test]
+  fn time_from_iana_timezone() {
+    let time = Time::new(1_464_600_000, "Europe/Paris").unwrap();
+    assert!((time.time_of_day() - 11.333_333).abs() < 1e-6);
+    assert_eq!(time.timezone(), "+02:00");
+    let time = Time::new(1_483_225_200, "Europe/Paris").unwrap();
+    assert_eq!(time.time_of_day(), 0.);
+    assert_eq!(time.day_of_week(), 6);
+    assert_eq!(time.day_of_month(), 1);
+    assert_eq!(time.month_of_year(), 1);
+    assert_eq!(time.timezone(), "+01:00");
+  }
+
+  #[test]
+  fn time_invalid_timezone() {
+    for timezone in ["Mars/Olympus_Mons", "+25:00", "+2:00", ""].iter() {
+      let error = Time::new(1_464_600_000, timezone).unwrap_err();
+      assert_eq!(
+        error.to_string(),
+        format!("Invalid argument - Invalid timezone '{}'", timezone)
+      );
+    }
+  }
+
+  #[cfg(feature = "tz")]
+  #[test]
+  fn time_fill_context() {
+    let configuration = ConfigurationBuilder::new()
+      .add_property("time", PropertyType::TimeOfDay, None, None)
+      .add_property("day", PropertyType::DayOfWeek, Some(true), None)
+      .add_property("month", PropertyType::MonthOfYear, Some(false), None)
+      .add_property("tz", PropertyType::Timezone, None, None)
+      .add_property("value", PropertyType::Continuous, None, None)
+      .set_output_property("value")
+      .create_agent_configuration()
+      .unwrap();
+    let mut context: Context = serde_json::from_value(json!({"month": 2, "value": 3})).unwrap();
+    Time::new(1_483_225_200, "Europe/Paris")
+      .unwrap()
+      .fill_context(&configuration, &mut context)
+      .unwrap();
+    assert_eq!(
+      context,
+      serde_json::from_value::<Context>(
+        json!({"time": 0., "day": 6, "month": 2, "tz": "+01:00", "value": 3})
+      )
+      .unwrap()
+    );
+  }
+
+  #[cfg(feature = "tz")]
+  #[test]
+  fn time_fill_context_with_context_timezone() {
+    let configuration = ConfigurationBuilder::new()
+      .add_property("time", PropertyType::TimeOfDay, None, None)
+      .add_property("day", PropertyType::DayOfWeek, None, None)
+      .add_property("tz", PropertyType::Timezone, None, None)
+      .add_property("value", PropertyType::Continuous, None, None)
+      .set_output_property("value")
+      .create_agent_configuration()
+      .unwrap();
+    // Midnight in Paris is still the previous day in New York
+    let mut context: Context =
+      serde_json::from_value(json!({"tz": "America/New_York", "value": 3})).unwrap();
+    Time::new(1_483_225_200, "Europe/Paris")
+      .unwrap()
+      .fill_context(&configuration, &mut context)
+      .unwrap();
+    assert_eq!(
+      context,
+      serde_json::from_value::<Context>(
+        json!({"time": 18., "day": 5, "tz": "America/New_York", "value": 3})
+      )
+      .unwrap()
+    );
+
+    let mut context: Context = serde_json::from_value(json!({"tz": "Mars/Olympus_Mons"})).unwrap();
+    let error = Time::new(1_483_225_200, "Europe/Paris")
+      .unwrap()
+      .fill_context(&configuration, &mut context)
+      .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "Invalid argument - Invalid timezone 'Mars/Olympus_Mons'"
+    );
+  }
+}
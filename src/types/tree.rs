@@ -0,0 +1,294 @@
+use crate::types::configuration::AgentConfiguration;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DecisionTreeVersion {
+  V1,
+  V2,
+}
+
+impl DecisionTreeVersion {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      DecisionTreeVersion::V1 => "1",
+      DecisionTreeVersion::V2 => "2",
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Operator {
+  #[serde(rename = "is")]
+  Is,
+  #[serde(rename = "in")]
+  In,
+  #[serde(rename = "[in[")]
+  InInterval,
+  #[serde(rename = "<")]
+  LessThan,
+  #[serde(rename = ">=")]
+  GreaterThanOrEqual,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DecisionRule {
+  pub property: String,
+  pub operator: Operator,
+  pub operand: Value,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Distribution {
+  Continuous {
+    standard_deviation: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mean: Option<f64>,
+  },
+  Enum(Vec<f64>),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Prediction {
+  pub value: Value,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub confidence: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub distribution: Option<Distribution>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub nb_samples: Option<u64>,
+}
+
+// V1 leaves hold their prediction inline, V2 leaves nest it in a `prediction` object.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawLeafNode {
+  V2 {
+    decision_rule: Option<DecisionRule>,
+    prediction: Prediction,
+  },
+  V1 {
+    decision_rule: Option<DecisionRule>,
+    predicted_value: Value,
+    confidence: Option<f64>,
+    standard_deviation: Option<f64>,
+  },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawLeafNode")]
+pub struct LeafNode {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub decision_rule: Option<DecisionRule>,
+  pub prediction: Prediction,
+}
+
+impl From<RawLeafNode> for LeafNode {
+  fn from(raw: RawLeafNode) -> LeafNode {
+    match raw {
+      RawLeafNode::V2 {
+        decision_rule,
+        prediction,
+      } => LeafNode {
+        decision_rule,
+        prediction,
+      },
+      RawLeafNode::V1 {
+        decision_rule,
+        predicted_value,
+        confidence,
+        standard_deviation,
+      } => LeafNode {
+        decision_rule,
+        prediction: Prediction {
+          value: predicted_value,
+          confidence,
+          distribution: standard_deviation.map(|standard_deviation| Distribution::Continuous {
+            standard_deviation,
+            mean: None,
+          }),
+          nb_samples: None,
+        },
+      },
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DecisionNode {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub decision_rule: Option<DecisionRule>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub output_values: Option<Vec<Value>>,
+  pub children: Vec<TreeNode>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TreeNode {
+  Decision(DecisionNode),
+  Leaf(LeafNode),
+}
+
+impl TreeNode {
+  pub fn decision_rule(&self) -> Option<&DecisionRule> {
+    match self {
+      TreeNode::Decision(decision_node) => decision_node.decision_rule.as_ref(),
+      TreeNode::Leaf(leaf_node) => leaf_node.decision_rule.as_ref(),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DecisionTree {
+  #[serde(rename = "_version")]
+  pub version: String,
+  pub configuration: AgentConfiguration,
+  pub trees: BTreeMap<String, TreeNode>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn decision_rule_from_json_string() {
+    assert_eq!(
+      serde_json::from_str::<DecisionRule>(
+        "{\"property\":\"light\",\"operator\":\"[in[\",\"operand\":[0,0.5]}"
+      )
+      .unwrap(),
+      DecisionRule {
+        property: "light".to_string(),
+        operator: Operator::InInterval,
+        operand: json!([0, 0.5]),
+      }
+    );
+    assert_eq!(
+      serde_json::from_str::<Operator>("\">=\"").unwrap(),
+      Operator::GreaterThanOrEqual
+    );
+  }
+
+  #[test]
+  fn decision_tree_v1_from_json_string() {
+    let tree = serde_json::from_value::<DecisionTree>(json!({
+      "_version": "1.1.0",
+      "configuration": {
+        "context": {
+          "light": {"type": "continuous"},
+          "state": {"type": "enum"}
+        },
+        "output": ["state"],
+        "time_quantum": 100
+      },
+      "trees": {
+        "state": {
+          "children": [
+            {
+              "decision_rule": {"property": "light", "operator": "<", "operand": 0.5},
+              "predicted_value": "OFF",
+              "confidence": 0.9
+            },
+            {
+              "decision_rule": {"property": "light", "operator": ">=", "operand": 0.5},
+              "predicted_value": "ON",
+              "confidence": 0.8
+            }
+          ]
+        }
+      }
+    }))
+    .unwrap();
+    assert_eq!(tree.version, "1.1.0");
+    assert_eq!(tree.configuration.output, ["state".to_string()]);
+    match &tree.trees["state"] {
+      TreeNode::Decision(root) => {
+        assert_eq!(root.decision_rule, None);
+        assert_eq!(root.children.len(), 2);
+        match &root.children[1] {
+          TreeNode::Leaf(leaf) => {
+            assert_eq!(
+              leaf.decision_rule.as_ref().unwrap().operator,
+              Operator::GreaterThanOrEqual
+            );
+            assert_eq!(leaf.prediction.value, json!("ON"));
+            assert_eq!(leaf.prediction.confidence, Some(0.8));
+          }
+          node => panic!("Unexpected node {:?}", node),
+        }
+      }
+      node => panic!("Unexpected node {:?}", node),
+    }
+  }
+
+  #[test]
+  fn decision_tree_v2_from_json_string() {
+    let tree = serde_json::from_value::<DecisionTree>(json!({
+      "_version": "2.0.0",
+      "configuration": {
+        "context": {
+          "light": {"type": "continuous"},
+          "power": {"type": "continuous"}
+        },
+        "output": ["power"]
+      },
+      "trees": {
+        "power": {
+          "children": [
+            {
+              "decision_rule": {"property": "light", "operator": "[in[", "operand": [0, 0.5]},
+              "children": [
+                {
+                  "decision_rule": {"property": "light", "operator": "<", "operand": 0.25},
+                  "prediction": {
+                    "value": 10.5,
+                    "confidence": 0.6,
+                    "distribution": {"standard_deviation": 1.5, "mean": 10.5},
+                    "nb_samples": 12
+                  }
+                },
+                {
+                  "decision_rule": {"property": "light", "operator": ">=", "operand": 0.25},
+                  "prediction": {"value": 12, "confidence": 0.7, "distribution": {"standard_deviation": 2}}
+                }
+              ]
+            },
+            {
+              "decision_rule": {"property": "light", "operator": "[in[", "operand": [0.5, 1]},
+              "prediction": {"value": null, "confidence": 0}
+            }
+          ]
+        }
+      }
+    }))
+    .unwrap();
+    let root = match &tree.trees["power"] {
+      TreeNode::Decision(root) => root,
+      node => panic!("Unexpected node {:?}", node),
+    };
+    match &root.children[0] {
+      TreeNode::Decision(node) => match &node.children[0] {
+        TreeNode::Leaf(leaf) => {
+          assert_eq!(
+            leaf.prediction.distribution,
+            Some(Distribution::Continuous {
+              standard_deviation: 1.5,
+              mean: Some(10.5)
+            })
+          );
+          assert_eq!(leaf.prediction.nb_samples, Some(12));
+        }
+        node => panic!("Unexpected node {:?}", node),
+      },
+      node => panic!("Unexpected node {:?}", node),
+    }
+    match &root.children[1] {
+      TreeNode::Leaf(leaf) => assert_eq!(leaf.prediction.value, Value::Null),
+      node => panic!("Unexpected node {:?}", node),
+    }
+  }
+}
@@ -1,9 +1,10 @@
 use crate::client::{Client, Method};
 use crate::error::Error;
+use crate::types::DecisionTreeVersion;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ApiInfo {
   #[serde(rename(deserialize = "craft_version"))]
   pub version: String,
@@ -15,3 +16,36 @@ pub async fn ping(client: &Client) -> Result<ApiInfo, Error> {
     .request_path::<&str, (), ApiInfo>(Method::GET, "/api/v1", None)
     .await
 }
+
+pub(crate) fn context_operations_query(
+  lower_bound: Option<u64>,
+  upper_bound: Option<u64>,
+) -> String {
+  let mut query = Vec::new();
+  if let Some(lower_bound) = lower_bound {
+    query.push(format!("lowerBound={}", lower_bound));
+  }
+  if let Some(upper_bound) = upper_bound {
+    query.push(format!("upperBound={}", upper_bound));
+  }
+  if query.is_empty() {
+    String::new()
+  } else {
+    format!("?{}", query.join("&"))
+  }
+}
+
+pub(crate) fn decision_tree_request(
+  timestamp: Option<u64>,
+  version: Option<DecisionTreeVersion>,
+) -> (String, Vec<(&'static str, String)>) {
+  let query_str = match timestamp {
+    Some(timestamp) => format!("?t={}", timestamp),
+    None => String::new(),
+  };
+  let headers = match version {
+    Some(version) => vec![("x-craft-ai-tree-version", version.as_str().to_string())],
+    None => vec![],
+  };
+  (query_str, headers)
+}
@@ -0,0 +1,49 @@
+#![allow(dead_code)]
+
+use craft_ai::mock::MockServer;
+use craft_ai::Client;
+#[cfg(feature = "blocking")]
+use craft_ai::{blocking, ClientBuilder};
+use dotenv::dotenv;
+use std::env;
+use std::sync::atomic::{AtomicU16, Ordering};
+#[cfg(feature = "blocking")]
+use tokio::runtime::Runtime;
+
+// Uses the platform pointed by `CRAFT_TOKEN` when defined, an in-process mock platform otherwise.
+pub fn setup_client() -> Client {
+  dotenv().ok();
+  match env::var("CRAFT_TOKEN") {
+    Ok(token) => Client::from_token(&token).unwrap(),
+    Err(_) => MockServer::start().unwrap().client().unwrap(),
+  }
+}
+
+// The mock platform runs on its own runtime, it must be kept alive as long as the client is used.
+#[cfg(feature = "blocking")]
+pub fn setup_blocking_client_builder() -> (ClientBuilder, Option<Runtime>) {
+  dotenv().ok();
+  match env::var("CRAFT_TOKEN") {
+    Ok(token) => (ClientBuilder::new(token), None),
+    Err(_) => {
+      let runtime = Runtime::new().unwrap();
+      let mock_server = runtime.enter(|| MockServer::start().unwrap());
+      (mock_server.client_builder(), Some(runtime))
+    }
+  }
+}
+
+#[cfg(feature = "blocking")]
+pub fn setup_blocking_client() -> (blocking::Client, Option<Runtime>) {
+  let (client_builder, runtime) = setup_blocking_client_builder();
+  (client_builder.create_blocking_client().unwrap(), runtime)
+}
+
+static TEST_ENTITY_COUNTER: AtomicU16 = AtomicU16::new(0);
+
+pub fn generate_entity_name() -> String {
+  format!(
+    "entity-{}",
+    TEST_ENTITY_COUNTER.fetch_add(1, Ordering::Relaxed)
+  )
+}
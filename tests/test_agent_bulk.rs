@@ -0,0 +1,50 @@
+use craft_ai::{
+  create_agents_bulk, delete_agents_bulk, ConfigurationBuilder, DeleteAgentResponse, PropertyType,
+};
+
+mod common;
+
+use crate::common::{generate_entity_name, setup_client};
+
+#[tokio::test]
+async fn create_and_delete_agents_bulk() {
+  let client = setup_client();
+  let agent_names: Vec<String> = (0..3).map(|_| generate_entity_name()).collect();
+
+  // Make sur the agents are deleted first
+  delete_agents_bulk(&client, &agent_names).await.unwrap();
+
+  // create the agents
+  let configuration = ConfigurationBuilder::new()
+    .add_property("x", PropertyType::Continuous, None, None)
+    .add_property("value", PropertyType::Boolean, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  let agents: Vec<(Option<&str>, _)> = agent_names
+    .iter()
+    .map(|agent_name| (Some(agent_name.as_str()), &configuration))
+    .collect();
+  let created_agents = create_agents_bulk(&client, &agents).await.unwrap();
+  assert_eq!(created_agents.len(), agent_names.len());
+  for (created_agent, agent_name) in created_agents.into_iter().zip(agent_names.iter()) {
+    assert_eq!(&created_agent.unwrap().name, agent_name);
+  }
+
+  // delete the agents, and a non existing one
+  let mut names_to_delete = agent_names.clone();
+  names_to_delete.push("this-agent-does-not-exist".to_string());
+  let deleted_agents = delete_agents_bulk(&client, &names_to_delete).await.unwrap();
+  assert_eq!(
+    deleted_agents
+      .into_iter()
+      .map(Result::unwrap)
+      .collect::<Vec<DeleteAgentResponse>>(),
+    vec![
+      DeleteAgentResponse::Deleted(agent_names[0].clone()),
+      DeleteAgentResponse::Deleted(agent_names[1].clone()),
+      DeleteAgentResponse::Deleted(agent_names[2].clone()),
+      DeleteAgentResponse::NonExisting("this-agent-does-not-exist".to_string()),
+    ]
+  );
+}
@@ -0,0 +1,286 @@
+use craft_ai::{
+  add_agent_context_operations, add_agent_context_operations_from_file,
+  add_agent_context_operations_from_reader, create_agent, delete_agent,
+  get_agent_context_operations, get_agent_context_state, ConfigurationBuilder, ContextOperation,
+  ContextValue, DeleteAgentResponse, Error, OperationsFileFormat, PropertyType,
+};
+use serde_json::json;
+
+mod common;
+
+use crate::common::{generate_entity_name, setup_client};
+
+#[tokio::test]
+async fn add_and_get_agent_context_operations() {
+  let client = setup_client();
+  let agent_name = generate_entity_name();
+
+  // Make sur the agent is deleted first
+  delete_agent(&client, &agent_name).await.unwrap();
+
+  // create the agent
+  let configuration = ConfigurationBuilder::new()
+    .add_property("tz", PropertyType::Timezone, None, None)
+    .add_property("presence", PropertyType::Enum, None, None)
+    .add_property("value", PropertyType::Continuous, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, &agent_name, &configuration)
+    .await
+    .unwrap();
+
+  // add more operations than a single chunk can hold
+  let operations: Vec<ContextOperation> = (0..450)
+    .map(|i| {
+      ContextOperation::new(
+        1_464_600_000 + i * 60,
+        serde_json::from_value(json!({
+          "tz": "+02:00",
+          "presence": if i % 2 == 0 { "home" } else { "away" },
+          "value": i as f64
+        }))
+        .unwrap(),
+      )
+    })
+    .collect();
+  for operation in operations.iter() {
+    operation.check(&configuration).unwrap();
+  }
+  add_agent_context_operations(&client, &agent_name, &operations)
+    .await
+    .unwrap();
+
+  // retrieve them
+  let retrieved_operations =
+    get_agent_context_operations(&client, &agent_name, Some(1_464_600_000), None)
+      .await
+      .unwrap();
+  assert_eq!(retrieved_operations.len(), operations.len());
+  assert_eq!(retrieved_operations[0].timestamp, 1_464_600_000);
+
+  // retrieve the merged state in between two operations
+  let context_state = get_agent_context_state(&client, &agent_name, 1_464_600_000 + 10 * 60 + 30)
+    .await
+    .unwrap();
+  assert_eq!(context_state.timestamp, 1_464_600_000 + 10 * 60);
+  assert_eq!(
+    context_state.context["presence"],
+    ContextValue::Enum("home".to_string())
+  );
+  assert_eq!(
+    context_state.context["tz"],
+    ContextValue::Timezone("+02:00".to_string())
+  );
+  assert_eq!(
+    context_state.context["value"],
+    ContextValue::Continuous(10.)
+  );
+
+  // delete the agent
+  assert_eq!(
+    delete_agent(&client, &agent_name).await.unwrap(),
+    DeleteAgentResponse::Deleted(agent_name)
+  )
+}
+
+#[tokio::test]
+async fn add_agent_context_operations_from_csv() {
+  let client = setup_client();
+  let agent_name = generate_entity_name();
+
+  delete_agent(&client, &agent_name).await.unwrap();
+  let configuration = ConfigurationBuilder::new()
+    .add_property("presence", PropertyType::Enum, None, None)
+    .add_property("value", PropertyType::Continuous, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, &agent_name, &configuration)
+    .await
+    .unwrap();
+
+  // rows don't need to be sorted
+  let csv = "timestamp,presence,value\n1464600120,away,2\n1464600000,home,0\n1464600060,,1\n";
+  let mut progress = Vec::new();
+  let uploaded_count = add_agent_context_operations_from_reader(
+    &client,
+    &agent_name,
+    csv.as_bytes(),
+    OperationsFileFormat::Csv,
+    |uploaded_count| progress.push(uploaded_count),
+  )
+  .await
+  .unwrap();
+  assert_eq!(uploaded_count, 3);
+  assert_eq!(progress, vec![3]);
+
+  let retrieved_operations = get_agent_context_operations(&client, &agent_name, None, None)
+    .await
+    .unwrap();
+  assert_eq!(
+    retrieved_operations
+      .iter()
+      .map(|operation| operation.timestamp)
+      .collect::<Vec<_>>(),
+    vec![1_464_600_000, 1_464_600_060, 1_464_600_120]
+  );
+  assert_eq!(retrieved_operations[1].context.get("presence"), None);
+
+  // invalid rows are rejected before anything is sent
+  let error = add_agent_context_operations_from_reader(
+    &client,
+    &agent_name,
+    "timestamp,value\n1464600180,high\n".as_bytes(),
+    OperationsFileFormat::Csv,
+    |_| {},
+  )
+  .await
+  .unwrap_err();
+  assert_eq!(
+    error.to_string(),
+    "Invalid argument - Invalid value 'high' for property 'value' of type 'continuous' at line 2"
+  );
+
+  assert_eq!(
+    delete_agent(&client, &agent_name).await.unwrap(),
+    DeleteAgentResponse::Deleted(agent_name)
+  )
+}
+
+#[tokio::test]
+async fn add_agent_context_operations_checks_the_configuration() {
+  let client = setup_client();
+  let agent_name = generate_entity_name();
+
+  delete_agent(&client, &agent_name).await.unwrap();
+  let configuration = ConfigurationBuilder::new()
+    .add_property("presence", PropertyType::Enum, None, None)
+    .add_property("value", PropertyType::Continuous, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, &agent_name, &configuration)
+    .await
+    .unwrap();
+
+  // the valid operation preceding the invalid one isn't uploaded either
+  let operations = vec![
+    ContextOperation::new(
+      1_464_600_000,
+      serde_json::from_value(json!({"presence": "home", "value": 1.})).unwrap(),
+    ),
+    ContextOperation::new(
+      1_464_600_060,
+      serde_json::from_value(json!({"presence": "home", "value": "high"})).unwrap(),
+    ),
+  ];
+  let error = add_agent_context_operations(&client, &agent_name, &operations)
+    .await
+    .unwrap_err();
+  assert_eq!(
+    error.to_string(),
+    "Invalid argument - Invalid value '\"high\"' for property 'value' of type 'continuous' in context operation at timestamp 1464600060"
+  );
+  assert!(
+    get_agent_context_operations(&client, &agent_name, None, None)
+      .await
+      .unwrap()
+      .is_empty()
+  );
+
+  assert_eq!(
+    delete_agent(&client, &agent_name).await.unwrap(),
+    DeleteAgentResponse::Deleted(agent_name)
+  )
+}
+
+// The last operation precedes the first batch, which is already uploaded when it's read
+fn unsorted_ndjson() -> String {
+  (0..10_000)
+    .chain(vec![-1])
+    .map(|i| {
+      format!(
+        "{{\"timestamp\":{},\"context\":{{\"value\":1}}}}\n",
+        1_464_600_000 + i
+      )
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn add_agent_context_operations_from_reader_reports_uploaded_count() {
+  let client = setup_client();
+  let agent_name = generate_entity_name();
+
+  delete_agent(&client, &agent_name).await.unwrap();
+  let configuration = ConfigurationBuilder::new()
+    .add_property("value", PropertyType::Continuous, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, &agent_name, &configuration)
+    .await
+    .unwrap();
+
+  match add_agent_context_operations_from_reader(
+    &client,
+    &agent_name,
+    std::io::Cursor::new(unsorted_ndjson()),
+    OperationsFileFormat::Ndjson,
+    |_| {},
+  )
+  .await
+  {
+    Err(Error::PartiallyUploaded {
+      uploaded_count,
+      error,
+    }) => {
+      assert_eq!(uploaded_count, 10_000);
+      assert!(matches!(*error, Error::InvalidArgument(_)));
+    }
+    result => panic!("Unexpected result {:?}", result),
+  }
+
+  assert_eq!(
+    delete_agent(&client, &agent_name).await.unwrap(),
+    DeleteAgentResponse::Deleted(agent_name)
+  )
+}
+
+#[tokio::test]
+async fn add_agent_context_operations_from_file_checks_the_whole_file() {
+  let client = setup_client();
+  let agent_name = generate_entity_name();
+
+  delete_agent(&client, &agent_name).await.unwrap();
+  let configuration = ConfigurationBuilder::new()
+    .add_property("value", PropertyType::Continuous, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, &agent_name, &configuration)
+    .await
+    .unwrap();
+
+  let path = std::env::temp_dir().join(format!("{}.ndjson", agent_name));
+  std::fs::write(&path, unsorted_ndjson()).unwrap();
+  let result = add_agent_context_operations_from_file(&client, &agent_name, &path, |_| {}).await;
+  std::fs::remove_file(&path).unwrap();
+  match result {
+    Err(Error::InvalidArgument(message)) => assert!(message.contains("sorted by timestamp")),
+    result => panic!("Unexpected result {:?}", result),
+  }
+  // nothing was uploaded
+  assert!(
+    get_agent_context_operations(&client, &agent_name, None, None)
+      .await
+      .unwrap()
+      .is_empty()
+  );
+
+  assert_eq!(
+    delete_agent(&client, &agent_name).await.unwrap(),
+    DeleteAgentResponse::Deleted(agent_name)
+  )
+}
@@ -0,0 +1,157 @@
+use craft_ai::mock::MockTransport;
+use craft_ai::{
+  add_agent_context_operations, create_agent, delete_agent, get_agent_decision_tree, Client,
+  ConfigurationBuilder, ContextOperation, DecisionTreeVersion, DeleteAgentResponse, Error,
+  PollingPolicy, PropertyType,
+};
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+
+use crate::common::{generate_entity_name, setup_client};
+
+#[tokio::test]
+async fn get_decision_tree() {
+  let client = setup_client();
+  let agent_name = generate_entity_name();
+
+  // Make sur the agent is deleted first
+  delete_agent(&client, &agent_name).await.unwrap();
+
+  // create the agent
+  let configuration = ConfigurationBuilder::new()
+    .add_property("light", PropertyType::Continuous, None, None)
+    .add_property("state", PropertyType::Enum, None, None)
+    .set_output_property("state")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, &agent_name, &configuration)
+    .await
+    .unwrap();
+
+  // add some operations
+  let operations: Vec<ContextOperation> = (0..20)
+    .map(|i| {
+      ContextOperation::new(
+        1_464_600_000 + i * 60,
+        serde_json::from_value(json!({
+          "light": (i % 4) as f64 / 4.,
+          "state": if i % 4 < 2 { "OFF" } else { "ON" }
+        }))
+        .unwrap(),
+      )
+    })
+    .collect();
+  add_agent_context_operations(&client, &agent_name, &operations)
+    .await
+    .unwrap();
+
+  // retrieve the tree
+  let tree = get_agent_decision_tree(
+    &client,
+    &agent_name,
+    Some(1_464_600_000 + 20 * 60),
+    Some(DecisionTreeVersion::V2),
+  )
+  .await
+  .unwrap();
+  assert!(tree.version.starts_with("2."));
+  assert_eq!(tree.configuration.output, configuration.output);
+  assert!(tree.trees.contains_key("state"));
+
+  // delete the agent
+  assert_eq!(
+    delete_agent(&client, &agent_name).await.unwrap(),
+    DeleteAgentResponse::Deleted(agent_name)
+  )
+}
+
+fn polling_client(mock_transport: &MockTransport, timeout: Duration) -> Client {
+  mock_transport
+    .client_builder()
+    .set_polling_policy(PollingPolicy {
+      interval: Duration::from_millis(10),
+      timeout,
+    })
+    .create_client()
+    .unwrap()
+}
+
+async fn create_trained_agent(client: &Client, agent_name: &str) {
+  let configuration = ConfigurationBuilder::new()
+    .add_property("light", PropertyType::Continuous, None, None)
+    .add_property("state", PropertyType::Enum, None, None)
+    .set_output_property("state")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(client, agent_name, &configuration)
+    .await
+    .unwrap();
+  let operations: Vec<ContextOperation> = (0..4)
+    .map(|i| {
+      ContextOperation::new(
+        1_464_600_000 + i * 60,
+        serde_json::from_value(json!({
+          "light": i as f64 / 4.,
+          "state": if i < 2 { "OFF" } else { "ON" }
+        }))
+        .unwrap(),
+      )
+    })
+    .collect();
+  add_agent_context_operations(client, agent_name, &operations)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn get_decision_tree_after_polling() {
+  let mock_transport = MockTransport::new();
+  let client = polling_client(&mock_transport, Duration::from_secs(10));
+  create_trained_agent(&client, "foo").await;
+
+  let requests_count = mock_transport.platform().requests_count();
+  mock_transport.platform().delay_computations(3, None);
+  let tree = get_agent_decision_tree(&client, "foo", Some(1_464_600_240), None)
+    .await
+    .unwrap();
+  assert!(tree.trees.contains_key("state"));
+  assert_eq!(
+    mock_transport.platform().requests_count(),
+    requests_count + 4
+  );
+}
+
+#[tokio::test]
+async fn get_decision_tree_polling_timeout() {
+  let mock_transport = MockTransport::new();
+  let client = polling_client(&mock_transport, Duration::from_millis(50));
+  create_trained_agent(&client, "foo").await;
+
+  mock_transport.platform().delay_computations(1000, None);
+  match get_agent_decision_tree(&client, "foo", None, None).await {
+    Err(Error::Timeout(_)) => {}
+    result => panic!("Unexpected result {:?}", result.map(|tree| tree.version)),
+  }
+}
+
+#[tokio::test]
+async fn get_decision_tree_polling_stays_on_the_platform() {
+  let mock_transport = MockTransport::new();
+  let client = polling_client(&mock_transport, Duration::from_secs(10));
+  create_trained_agent(&client, "foo").await;
+
+  let requests_count = mock_transport.platform().requests_count();
+  mock_transport
+    .platform()
+    .delay_computations(1, Some("http://elsewhere.example.com/tree"));
+  match get_agent_decision_tree(&client, "foo", None, None).await {
+    Err(Error::InternalError(message, None)) => assert!(message.contains("elsewhere")),
+    result => panic!("Unexpected result {:?}", result.map(|tree| tree.version)),
+  }
+  assert_eq!(
+    mock_transport.platform().requests_count(),
+    requests_count + 1
+  );
+}
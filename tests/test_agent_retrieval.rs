@@ -0,0 +1,78 @@
+use craft_ai::{
+  create_agent, create_agents_bulk, delete_agent, delete_agents_bulk, get_agent, list_agents,
+  list_agents_stream, ConfigurationBuilder, DeleteAgentResponse, PropertyType,
+};
+use futures::stream::TryStreamExt;
+
+mod common;
+
+use crate::common::{generate_entity_name, setup_client};
+
+#[tokio::test]
+async fn get_and_list_agents() {
+  let client = setup_client();
+  let agent_name = generate_entity_name();
+
+  // Make sur the agent is deleted first
+  delete_agent(&client, &agent_name).await.unwrap();
+
+  // create the agent
+  let configuration = ConfigurationBuilder::new()
+    .add_property("x", PropertyType::Continuous, None, None)
+    .add_property("value", PropertyType::Boolean, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, &agent_name, &configuration)
+    .await
+    .unwrap();
+
+  // retrieve the agent
+  let agent = get_agent(&client, &agent_name).await.unwrap();
+  assert_eq!(agent.name, agent_name);
+  assert_eq!(agent.configuration.output, configuration.output);
+  assert_eq!(agent.first_timestamp, None);
+
+  // list the agents
+  let agents = list_agents(&client).await.unwrap();
+  assert!(agents.contains(&agent_name));
+  let streamed_agents: Vec<String> = list_agents_stream(&client).try_collect().await.unwrap();
+  assert_eq!(streamed_agents, agents);
+
+  // delete the agent
+  assert_eq!(
+    delete_agent(&client, &agent_name).await.unwrap(),
+    DeleteAgentResponse::Deleted(agent_name)
+  )
+}
+
+#[tokio::test]
+async fn list_agents_over_several_pages() {
+  let client = setup_client();
+  // more agents than a single page of the listing holds
+  let agent_names: Vec<String> = (0..250).map(|_| generate_entity_name()).collect();
+  delete_agents_bulk(&client, &agent_names).await.unwrap();
+
+  let configuration = ConfigurationBuilder::new()
+    .add_property("x", PropertyType::Continuous, None, None)
+    .add_property("value", PropertyType::Boolean, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  let agents: Vec<_> = agent_names
+    .iter()
+    .map(|agent_name| (Some(agent_name.as_str()), &configuration))
+    .collect();
+  for created_agent in create_agents_bulk(&client, &agents).await.unwrap() {
+    created_agent.unwrap();
+  }
+
+  let listed_agents = list_agents(&client).await.unwrap();
+  for agent_name in agent_names.iter() {
+    assert!(listed_agents.contains(agent_name));
+  }
+  let streamed_agents: Vec<String> = list_agents_stream(&client).try_collect().await.unwrap();
+  assert_eq!(streamed_agents, listed_agents);
+
+  delete_agents_bulk(&client, &agent_names).await.unwrap();
+}
@@ -0,0 +1,160 @@
+use craft_ai::blocking::{
+  add_agent_context_operations, create_agent, delete_agent, get_agent,
+  get_agent_context_operations, get_agent_decision_tree, list_agents, ping,
+};
+use craft_ai::mock::MockServer;
+use craft_ai::{
+  ConfigurationBuilder, ContextOperation, DecisionTreeVersion, DeleteAgentResponse, Error,
+  HttpRequest, HttpResponse, Interceptor, PollingPolicy, PropertyType, StatusCode,
+};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+mod common;
+
+use crate::common::{generate_entity_name, setup_blocking_client, setup_blocking_client_builder};
+
+struct StatusLog {
+  statuses: Arc<Mutex<Vec<Option<StatusCode>>>>,
+}
+
+impl Interceptor for StatusLog {
+  fn on_response(
+    &self,
+    _request: &HttpRequest,
+    response: Result<&HttpResponse, &Error>,
+    _elapsed: Duration,
+  ) {
+    self
+      .statuses
+      .lock()
+      .unwrap()
+      .push(response.ok().map(|response| response.status));
+  }
+}
+
+#[test]
+fn blocking_ping() {
+  let (client, _runtime) = setup_blocking_client();
+  let api_info = ping(&client).unwrap();
+  assert!(!api_info.version.is_empty());
+}
+
+#[test]
+fn blocking_agent_lifecycle() {
+  let (client, _runtime) = setup_blocking_client();
+  let agent_name = generate_entity_name();
+
+  // Make sur the agent is deleted first
+  delete_agent(&client, &agent_name).unwrap();
+
+  // create the agent
+  let configuration = ConfigurationBuilder::new()
+    .add_property("light", PropertyType::Continuous, None, None)
+    .add_property("state", PropertyType::Enum, None, None)
+    .set_output_property("state")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, &agent_name, &configuration).unwrap();
+  assert_eq!(get_agent(&client, &agent_name).unwrap().name, agent_name);
+  assert!(list_agents(&client).unwrap().contains(&agent_name));
+
+  // add some operations
+  let operations: Vec<ContextOperation> = (0..20)
+    .map(|i| {
+      ContextOperation::new(
+        1_464_600_000 + i * 60,
+        serde_json::from_value(json!({
+          "light": (i % 4) as f64 / 4.,
+          "state": if i % 4 < 2 { "OFF" } else { "ON" }
+        }))
+        .unwrap(),
+      )
+    })
+    .collect();
+  add_agent_context_operations(&client, &agent_name, &operations).unwrap();
+  assert_eq!(
+    get_agent_context_operations(&client, &agent_name, None, None)
+      .unwrap()
+      .len(),
+    20
+  );
+
+  // retrieve the tree
+  let tree = get_agent_decision_tree(
+    &client,
+    &agent_name,
+    Some(1_464_600_000 + 20 * 60),
+    Some(DecisionTreeVersion::V2),
+  )
+  .unwrap();
+  assert!(tree.trees.contains_key("state"));
+
+  // delete the agent
+  assert_eq!(
+    delete_agent(&client, &agent_name).unwrap(),
+    DeleteAgentResponse::Deleted(agent_name.clone())
+  );
+  match get_agent(&client, &agent_name) {
+    Err(Error::NotFound(_)) => {}
+    result => panic!("Unexpected result {:?}", result.map(|agent| agent.name)),
+  }
+}
+
+#[test]
+fn blocking_interceptors() {
+  let (mut client_builder, _runtime) = setup_blocking_client_builder();
+  let statuses = Arc::new(Mutex::new(Vec::new()));
+  let client = client_builder
+    .add_interceptor(StatusLog {
+      statuses: statuses.clone(),
+    })
+    .create_blocking_client()
+    .unwrap();
+  ping(&client).unwrap();
+  get_agent(&client, generate_entity_name()).unwrap_err();
+  assert_eq!(
+    *statuses.lock().unwrap(),
+    vec![Some(StatusCode::OK), Some(StatusCode::NOT_FOUND)]
+  );
+}
+
+#[test]
+fn blocking_decision_tree_polling() {
+  let runtime = Runtime::new().unwrap();
+  let mock_server = runtime.enter(|| MockServer::start().unwrap());
+  let client = mock_server
+    .client_builder()
+    .set_polling_policy(PollingPolicy {
+      interval: Duration::from_millis(10),
+      timeout: Duration::from_millis(200),
+    })
+    .create_blocking_client()
+    .unwrap();
+  let configuration = ConfigurationBuilder::new()
+    .add_property("light", PropertyType::Continuous, None, None)
+    .add_property("state", PropertyType::Enum, None, None)
+    .set_output_property("state")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, "foo", &configuration).unwrap();
+  let operations = vec![ContextOperation::new(
+    1_464_600_000,
+    serde_json::from_value(json!({"light": 0.5, "state": "ON"})).unwrap(),
+  )];
+  add_agent_context_operations(&client, "foo", &operations).unwrap();
+
+  let requests_count = mock_server.platform().requests_count();
+  mock_server.platform().delay_computations(2, None);
+  let tree = get_agent_decision_tree(&client, "foo", None, None).unwrap();
+  assert!(tree.trees.contains_key("state"));
+  assert_eq!(mock_server.platform().requests_count(), requests_count + 3);
+
+  mock_server.platform().delay_computations(1000, None);
+  match get_agent_decision_tree(&client, "foo", None, None) {
+    Err(Error::Timeout(_)) => {}
+    result => panic!("Unexpected result {:?}", result.map(|tree| tree.version)),
+  }
+}
@@ -0,0 +1,82 @@
+use craft_ai::{
+  add_agent_context_operations, create_agent, create_generator, delete_agent, delete_generator,
+  get_generator, get_generator_context_operations, list_generators, ConfigurationBuilder,
+  ContextOperation, DeleteGeneratorResponse, PropertyType,
+};
+use serde_json::json;
+
+mod common;
+
+use crate::common::{generate_entity_name, setup_client};
+
+#[tokio::test]
+async fn create_get_and_delete_generator() {
+  let client = setup_client();
+  let agent_name = generate_entity_name();
+  let generator_name = generate_entity_name();
+
+  // Make sur the agent and the generator are deleted first
+  delete_agent(&client, &agent_name).await.unwrap();
+  delete_generator(&client, &generator_name).await.unwrap();
+
+  // create an agent with some operations
+  let mut configuration_builder = ConfigurationBuilder::new();
+  configuration_builder
+    .add_property("x", PropertyType::Continuous, None, None)
+    .add_property("value", PropertyType::Continuous, None, None)
+    .set_output_property("value");
+  create_agent(
+    &client,
+    &agent_name,
+    &configuration_builder.create_agent_configuration().unwrap(),
+  )
+  .await
+  .unwrap();
+  let operations: Vec<ContextOperation> = (0..10)
+    .map(|i| {
+      ContextOperation::new(
+        1_464_600_000 + i * 60,
+        serde_json::from_value(json!({"x": i as f64, "value": 2. * i as f64})).unwrap(),
+      )
+    })
+    .collect();
+  add_agent_context_operations(&client, &agent_name, &operations)
+    .await
+    .unwrap();
+
+  // create the generator
+  let generator_configuration = configuration_builder
+    .create_generator_configuration(vec![agent_name.clone()])
+    .unwrap();
+  let created_generator = create_generator(&client, &generator_name, &generator_configuration)
+    .await
+    .unwrap();
+  assert_eq!(created_generator.name, generator_name);
+  assert_eq!(
+    created_generator.configuration.filter,
+    vec![agent_name.clone()]
+  );
+
+  // retrieve it
+  let generator = get_generator(&client, &generator_name).await.unwrap();
+  assert_eq!(
+    generator.configuration.output,
+    generator_configuration.output
+  );
+  assert!(list_generators(&client)
+    .await
+    .unwrap()
+    .contains(&generator_name));
+  let generator_operations = get_generator_context_operations(&client, &generator_name, None, None)
+    .await
+    .unwrap();
+  assert_eq!(generator_operations.len(), operations.len());
+  assert_eq!(generator_operations[0].agent_name, agent_name);
+
+  // delete the generator and the agent
+  assert_eq!(
+    delete_generator(&client, &generator_name).await.unwrap(),
+    DeleteGeneratorResponse::Deleted(generator_name)
+  );
+  delete_agent(&client, &agent_name).await.unwrap();
+}
@@ -0,0 +1,92 @@
+use craft_ai::mock::MockTransport;
+use craft_ai::{
+  get_agent, list_agents, ping, Error, HttpRequest, HttpResponse, Interceptor, StatusCode,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Url, trace id and status of each response
+type ResponseLogEntry = (String, String, Option<StatusCode>);
+
+#[derive(Default)]
+struct ResponseLog {
+  entries: Arc<Mutex<Vec<ResponseLogEntry>>>,
+}
+
+impl Interceptor for ResponseLog {
+  fn on_response(
+    &self,
+    request: &HttpRequest,
+    response: Result<&HttpResponse, &Error>,
+    _elapsed: Duration,
+  ) {
+    let trace_id = request
+      .headers
+      .get("x-trace-id")
+      .and_then(|trace_id| trace_id.to_str().ok())
+      .unwrap_or_default()
+      .to_string();
+    self.entries.lock().unwrap().push((
+      request.url.clone(),
+      trace_id,
+      response.ok().map(|response| response.status),
+    ));
+  }
+}
+
+#[tokio::test]
+async fn interceptors_alter_requests_and_observe_responses() {
+  let response_log = ResponseLog::default();
+  let entries = response_log.entries.clone();
+  let client = MockTransport::new()
+    .client_builder()
+    .add_interceptor(|request: &mut HttpRequest| {
+      request
+        .headers
+        .insert("x-trace-id", "trace-1234".parse().unwrap());
+      Ok(())
+    })
+    .add_interceptor(response_log)
+    .create_client()
+    .unwrap();
+
+  ping(&client).await.unwrap();
+  list_agents(&client).await.unwrap();
+  get_agent(&client, "foo").await.unwrap_err();
+
+  assert_eq!(
+    *entries.lock().unwrap(),
+    vec![
+      (
+        "http://mock.craft.ai/api/v1".to_string(),
+        "trace-1234".to_string(),
+        Some(StatusCode::OK)
+      ),
+      (
+        "http://mock.craft.ai/api/v1/mock-owner/mock-project/agents".to_string(),
+        "trace-1234".to_string(),
+        Some(StatusCode::OK)
+      ),
+      (
+        "http://mock.craft.ai/api/v1/mock-owner/mock-project/agents/foo".to_string(),
+        "trace-1234".to_string(),
+        Some(StatusCode::NOT_FOUND)
+      ),
+    ]
+  );
+}
+
+#[tokio::test]
+async fn interceptors_can_abort_requests() {
+  let client = MockTransport::new()
+    .client_builder()
+    .add_interceptor(|_request: &mut HttpRequest| {
+      Err(Error::InvalidArgument("Missing trace id".to_string()))
+    })
+    .create_client()
+    .unwrap();
+  assert_eq!(
+    ping(&client).await.unwrap_err().to_string(),
+    "Invalid argument - Missing trace id"
+  );
+}
@@ -8,5 +8,5 @@ use crate::common::setup_client;
 async fn ping_works() {
   let client = setup_client();
   let ping_response = ping(&client).await.unwrap();
-  assert!(ping_response.version != "");
+  assert!(!ping_response.version.is_empty());
 }
@@ -0,0 +1,176 @@
+use craft_ai::mock::{MockFailure, MockTransport};
+use craft_ai::{
+  create_agent, delete_agent, get_agent, Client, ConfigurationBuilder, ContextOperation,
+  DeleteAgentResponse, Error, Method, PropertyType, RetryPolicy, StatusCode,
+};
+use serde::de::IgnoredAny;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+fn retrying_client(mock_transport: &MockTransport, max_backoff: Duration) -> Client {
+  retrying_client_with_writes(mock_transport, max_backoff, false)
+}
+
+fn retrying_client_with_writes(
+  mock_transport: &MockTransport,
+  max_backoff: Duration,
+  retry_writes: bool,
+) -> Client {
+  mock_transport
+    .client_builder()
+    .set_retry_policy(RetryPolicy {
+      max_attempts: 3,
+      initial_backoff: Duration::from_millis(1),
+      max_backoff,
+      retry_writes,
+    })
+    .create_client()
+    .unwrap()
+}
+
+fn failure(status: StatusCode, retry_after: Option<u64>) -> MockFailure {
+  MockFailure {
+    status,
+    retry_after,
+  }
+}
+
+async fn create_test_agent(client: &Client, name: &str) -> Result<String, Error> {
+  let configuration = ConfigurationBuilder::new()
+    .add_property("value", PropertyType::Continuous, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(client, name, &configuration)
+    .await
+    .map(|agent| agent.name)
+}
+
+#[tokio::test]
+async fn reads_are_retried_on_server_errors() {
+  let mock_transport = MockTransport::new();
+  let client = retrying_client(&mock_transport, Duration::from_millis(1));
+
+  mock_transport
+    .platform()
+    .push_failures(failure(StatusCode::SERVICE_UNAVAILABLE, None), 2);
+  match get_agent(&client, "foo").await {
+    Err(Error::NotFound(_)) => {}
+    result => panic!("Unexpected result {:?}", result.map(|agent| agent.name)),
+  }
+  assert_eq!(mock_transport.platform().requests_count(), 3);
+
+  // the last attempt's error is returned once they are all used
+  mock_transport
+    .platform()
+    .push_failures(failure(StatusCode::INTERNAL_SERVER_ERROR, None), 3);
+  match get_agent(&client, "foo").await {
+    Err(Error::ServerError(request)) => {
+      assert_eq!(request.status, StatusCode::INTERNAL_SERVER_ERROR)
+    }
+    result => panic!("Unexpected result {:?}", result.map(|agent| agent.name)),
+  }
+  assert_eq!(mock_transport.platform().requests_count(), 6);
+}
+
+#[tokio::test]
+async fn writes_are_only_retried_when_rate_limited() {
+  let mock_transport = MockTransport::new();
+  let client = retrying_client(&mock_transport, Duration::from_millis(1));
+
+  mock_transport
+    .platform()
+    .push_failures(failure(StatusCode::BAD_GATEWAY, None), 1);
+  match create_test_agent(&client, "foo").await {
+    Err(Error::ServerError(_)) => {}
+    result => panic!("Unexpected result {:?}", result),
+  }
+  assert_eq!(mock_transport.platform().requests_count(), 1);
+
+  mock_transport
+    .platform()
+    .push_failures(failure(StatusCode::TOO_MANY_REQUESTS, None), 2);
+  assert_eq!(create_test_agent(&client, "foo").await.unwrap(), "foo");
+  assert_eq!(mock_transport.platform().requests_count(), 4);
+
+  mock_transport
+    .platform()
+    .push_failures(failure(StatusCode::TOO_MANY_REQUESTS, Some(0)), 3);
+  match create_test_agent(&client, "bar").await {
+    Err(Error::RateLimited { retry_after, .. }) => assert_eq!(retry_after, Some(Duration::ZERO)),
+    result => panic!("Unexpected result {:?}", result),
+  }
+  assert_eq!(mock_transport.platform().requests_count(), 7);
+}
+
+#[tokio::test]
+async fn retry_after_is_followed_up_to_the_max_backoff() {
+  let mock_transport = MockTransport::new();
+
+  let client = retrying_client(&mock_transport, Duration::from_secs(10));
+  mock_transport
+    .platform()
+    .push_failures(failure(StatusCode::TOO_MANY_REQUESTS, Some(1)), 1);
+  let started_at = Instant::now();
+  get_agent(&client, "foo").await.unwrap_err();
+  assert!(started_at.elapsed() >= Duration::from_secs(1));
+  assert_eq!(mock_transport.platform().requests_count(), 2);
+
+  let client = retrying_client(&mock_transport, Duration::from_millis(10));
+  mock_transport
+    .platform()
+    .push_failures(failure(StatusCode::TOO_MANY_REQUESTS, Some(3600)), 1);
+  let started_at = Instant::now();
+  get_agent(&client, "foo").await.unwrap_err();
+  assert!(started_at.elapsed() < Duration::from_secs(1));
+  assert_eq!(mock_transport.platform().requests_count(), 4);
+}
+
+#[tokio::test]
+async fn deletions_are_retried_on_server_errors() {
+  let mock_transport = MockTransport::new();
+  let client = retrying_client(&mock_transport, Duration::from_millis(1));
+  create_test_agent(&client, "foo").await.unwrap();
+
+  mock_transport
+    .platform()
+    .push_failures(failure(StatusCode::SERVICE_UNAVAILABLE, None), 1);
+  assert_eq!(
+    delete_agent(&client, "foo").await.unwrap(),
+    DeleteAgentResponse::Deleted("foo".to_string())
+  );
+  assert_eq!(mock_transport.platform().requests_count(), 3);
+}
+
+async fn upload_test_operations(client: &Client) -> Result<IgnoredAny, Error> {
+  let operations = vec![ContextOperation::new(
+    1_464_600_000,
+    serde_json::from_value(json!({"value": 1.})).unwrap(),
+  )];
+  client
+    .request_project::<&str, _, IgnoredAny>(Method::POST, "/agents/foo/context", Some(&operations))
+    .await
+}
+
+#[tokio::test]
+async fn uploads_are_retried_when_writes_are() {
+  let mock_transport = MockTransport::new();
+  let client = retrying_client(&mock_transport, Duration::from_millis(1));
+  create_test_agent(&client, "foo").await.unwrap();
+
+  mock_transport
+    .platform()
+    .push_failures(failure(StatusCode::SERVICE_UNAVAILABLE, None), 1);
+  match upload_test_operations(&client).await {
+    Err(Error::ServerError(_)) => {}
+    result => panic!("Unexpected result {:?}", result.map(|_| ())),
+  }
+  assert_eq!(mock_transport.platform().requests_count(), 2);
+
+  let client = retrying_client_with_writes(&mock_transport, Duration::from_millis(1), true);
+  mock_transport
+    .platform()
+    .push_failures(failure(StatusCode::SERVICE_UNAVAILABLE, None), 1);
+  upload_test_operations(&client).await.unwrap();
+  assert_eq!(mock_transport.platform().requests_count(), 4);
+}
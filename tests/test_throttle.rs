@@ -0,0 +1,144 @@
+use craft_ai::mock::MockTransport;
+use craft_ai::{ping, ClientBuilder, Error, HttpRequest, HttpResponse, RateLimit, Transport};
+use futures::future::{join_all, BoxFuture};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::delay_for;
+
+// Slow transport keeping track of the maximum count of concurrent requests
+#[derive(Clone, Default)]
+struct SlowTransport {
+  mock_transport: MockTransport,
+  in_flight: Arc<AtomicUsize>,
+  max_in_flight: Arc<AtomicUsize>,
+}
+
+impl Transport for SlowTransport {
+  fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>> {
+    Box::pin(async move {
+      let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+      self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+      delay_for(Duration::from_millis(20)).await;
+      let response = self.mock_transport.send(request).await;
+      self.in_flight.fetch_sub(1, Ordering::SeqCst);
+      response
+    })
+  }
+}
+
+fn client_builder(transport: &SlowTransport) -> ClientBuilder {
+  let mut client_builder = transport.mock_transport.client_builder();
+  client_builder.set_transport(transport.clone());
+  client_builder
+}
+
+#[tokio::test]
+async fn max_in_flight_is_enforced() {
+  let transport = SlowTransport::default();
+  let client = client_builder(&transport)
+    .set_max_in_flight(3)
+    .create_client()
+    .unwrap();
+  let results = join_all((0..12).map(|_| ping(&client))).await;
+  assert!(results.iter().all(Result::is_ok));
+  assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn rate_limit_is_enforced() {
+  let transport = SlowTransport::default();
+  let client = client_builder(&transport)
+    .set_rate_limit(RateLimit {
+      requests_per_second: 50.,
+      burst: 2,
+    })
+    .create_client()
+    .unwrap();
+  let started_at = Instant::now();
+  let results = join_all((0..7).map(|_| ping(&client))).await;
+  assert!(results.iter().all(Result::is_ok));
+  // 2 requests are sent right away, the 5 others are spaced by 20ms
+  assert!(started_at.elapsed() >= Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn interceptors_run_once_throttled() {
+  let transport = SlowTransport::default();
+  let intercepted_at = Arc::new(Mutex::new(Vec::new()));
+  let intercepted_at_clone = intercepted_at.clone();
+  let client = client_builder(&transport)
+    .set_rate_limit(RateLimit {
+      requests_per_second: 20.,
+      burst: 1,
+    })
+    .add_interceptor(move |_request: &mut HttpRequest| {
+      intercepted_at_clone.lock().unwrap().push(Instant::now());
+      Ok(())
+    })
+    .create_client()
+    .unwrap();
+  let results = join_all((0..3).map(|_| ping(&client))).await;
+  assert!(results.iter().all(Result::is_ok));
+  // Requests are intercepted when they are sent, spaced by 50ms, not while waiting for the limit
+  let intercepted_at = intercepted_at.lock().unwrap();
+  assert_eq!(intercepted_at.len(), 3);
+  for (previous, next) in intercepted_at.iter().zip(intercepted_at.iter().skip(1)) {
+    assert!(next.saturating_duration_since(*previous) >= Duration::from_millis(40));
+  }
+}
+
+#[test]
+fn invalid_limits() {
+  let transport = SlowTransport::default();
+  let error = client_builder(&transport)
+    .set_max_in_flight(0)
+    .create_client()
+    .err()
+    .unwrap();
+  assert_eq!(
+    error.to_string(),
+    "Invalid argument - 'max_in_flight' must be strictly positive"
+  );
+  let error = client_builder(&transport)
+    .set_rate_limit(RateLimit {
+      requests_per_second: 0.,
+      burst: 1,
+    })
+    .create_client()
+    .err()
+    .unwrap();
+  assert_eq!(
+    error.to_string(),
+    "Invalid argument - 'requests_per_second' must be strictly positive, got 0"
+  );
+  // Neither the semaphore nor the waits for tokens can handle extreme limits
+  let error = client_builder(&transport)
+    .set_max_in_flight(usize::MAX)
+    .create_client()
+    .err()
+    .unwrap();
+  assert_eq!(
+    error.to_string(),
+    format!(
+      "Invalid argument - 'max_in_flight' must be at most {}, got {}",
+      usize::MAX >> 3,
+      usize::MAX
+    )
+  );
+  assert!(client_builder(&transport)
+    .set_max_in_flight(usize::MAX >> 3)
+    .create_client()
+    .is_ok());
+  let error = client_builder(&transport)
+    .set_rate_limit(RateLimit {
+      requests_per_second: 1e-20,
+      burst: 1,
+    })
+    .create_client()
+    .err()
+    .unwrap();
+  assert!(error
+    .to_string()
+    .starts_with("Invalid argument - 'requests_per_second' must be at least one request a day"));
+}
@@ -0,0 +1,90 @@
+use craft_ai::mock::MockTransport;
+use craft_ai::{create_agent, get_agent, ConfigurationBuilder, PropertyType};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+type SpanFields = BTreeMap<String, String>;
+
+// Minimal subscriber keeping the fields of every span
+#[derive(Clone, Default)]
+struct SpanRecorder {
+  spans: Arc<Mutex<Vec<SpanFields>>>,
+}
+
+struct FieldVisitor<'a>(&'a mut SpanFields);
+
+impl<'a> Visit for FieldVisitor<'a> {
+  fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+    self
+      .0
+      .insert(field.name().to_string(), format!("{:?}", value));
+  }
+}
+
+impl Subscriber for SpanRecorder {
+  fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+    true
+  }
+
+  fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+    let mut spans = self.spans.lock().unwrap();
+    let mut fields = SpanFields::new();
+    fields.insert("name".to_string(), attributes.metadata().name().to_string());
+    attributes.record(&mut FieldVisitor(&mut fields));
+    spans.push(fields);
+    Id::from_u64(spans.len() as u64)
+  }
+
+  fn record(&self, span: &Id, values: &Record<'_>) {
+    let mut spans = self.spans.lock().unwrap();
+    values.record(&mut FieldVisitor(&mut spans[span.into_u64() as usize - 1]));
+  }
+
+  fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+  fn event(&self, _event: &Event<'_>) {}
+
+  fn enter(&self, _span: &Id) {}
+
+  fn exit(&self, _span: &Id) {}
+}
+
+#[tokio::test]
+async fn every_call_is_traced() {
+  let recorder = SpanRecorder::default();
+  let _guard = tracing::subscriber::set_default(recorder.clone());
+  let mock_transport = MockTransport::new();
+  let client = mock_transport.client().unwrap();
+  let configuration = ConfigurationBuilder::new()
+    .add_property("value", PropertyType::Continuous, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, "foo", &configuration).await.unwrap();
+  get_agent(&client, "bar").await.unwrap_err();
+
+  let spans = recorder.spans.lock().unwrap();
+  assert_eq!(spans.len(), 2);
+  for span in spans.iter() {
+    assert_eq!(span["name"], "craft_ai.request");
+    assert_eq!(span["owner"], "\"mock-owner\"");
+    assert_eq!(span["project"], "\"mock-project\"");
+    assert_eq!(span["retries"], "0");
+    assert!(span.contains_key("latency_ms"));
+    assert!(!span
+      .values()
+      .any(|value| value.contains(&mock_transport.token())));
+  }
+  assert_eq!(spans[0]["method"], "POST");
+  assert_eq!(spans[0]["path"], "/agents");
+  assert_eq!(spans[0]["status"], "201");
+  assert_ne!(spans[0]["request_size"], "0");
+  assert_eq!(spans[1]["method"], "GET");
+  assert_eq!(spans[1]["path"], "/agents/{id}");
+  assert_eq!(spans[1]["status"], "404");
+  assert_eq!(spans[1]["request_size"], "0");
+}
@@ -0,0 +1,121 @@
+use craft_ai::mock::MockTransport;
+use craft_ai::{
+  create_agent, get_agent, ConfigurationBuilder, Error, HttpRequest, HttpResponse, PropertyType,
+  RetryPolicy, Transport,
+};
+use futures::future::BoxFuture;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Records the sent requests, the first `failures` ones fail without reaching the mock platform
+struct RecordingTransport {
+  mock_transport: MockTransport,
+  requests: Arc<Mutex<Vec<HttpRequest>>>,
+  failures: AtomicUsize,
+}
+
+impl Transport for RecordingTransport {
+  fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>> {
+    self.requests.lock().unwrap().push(request.clone());
+    if self.failures.load(Ordering::SeqCst) > 0 {
+      self.failures.fetch_sub(1, Ordering::SeqCst);
+      return Box::pin(async {
+        Err(Error::NetworkError(
+          "Connection reset".to_string(),
+          Box::new(std::io::Error::from(std::io::ErrorKind::ConnectionReset)),
+        ))
+      });
+    }
+    self.mock_transport.send(request)
+  }
+}
+
+fn recording_client(failures: usize) -> (craft_ai::Client, Arc<Mutex<Vec<HttpRequest>>>) {
+  let mock_transport = MockTransport::new();
+  let requests = Arc::new(Mutex::new(Vec::new()));
+  let client = mock_transport
+    .client_builder()
+    .set_user_agent("my-service/1.0")
+    .set_retry_policy(RetryPolicy {
+      max_attempts: 3,
+      initial_backoff: Duration::from_millis(1),
+      max_backoff: Duration::from_millis(1),
+      retry_writes: false,
+    })
+    .set_transport(RecordingTransport {
+      mock_transport,
+      requests: requests.clone(),
+      failures: AtomicUsize::new(failures),
+    })
+    .create_client()
+    .unwrap();
+  (client, requests)
+}
+
+#[tokio::test]
+async fn custom_transport_receives_prepared_requests() {
+  let (client, requests) = recording_client(0);
+  let configuration = ConfigurationBuilder::new()
+    .add_property("value", PropertyType::Continuous, None, None)
+    .set_output_property("value")
+    .create_agent_configuration()
+    .unwrap();
+  create_agent(&client, "foo", &configuration).await.unwrap();
+  assert_eq!(get_agent(&client, "foo").await.unwrap().name, "foo");
+
+  let requests = requests.lock().unwrap();
+  assert_eq!(requests.len(), 2);
+  assert_eq!(requests[0].method, craft_ai::Method::POST);
+  assert_eq!(
+    requests[0].url,
+    "http://mock.craft.ai/api/v1/mock-owner/mock-project/agents"
+  );
+  assert_eq!(requests[0].headers["content-type"], "application/json");
+  assert_eq!(requests[0].headers["user-agent"], "my-service/1.0");
+  assert!(requests[0].headers["authorization"]
+    .to_str()
+    .unwrap()
+    .starts_with("Bearer "));
+  assert!(requests[0].body.is_some());
+  assert_eq!(requests[1].method, craft_ai::Method::GET);
+  assert!(requests[1].body.is_none());
+}
+
+#[tokio::test]
+async fn custom_transport_network_errors_are_retried() {
+  let (client, requests) = recording_client(2);
+  match get_agent(&client, "foo").await {
+    Err(Error::NotFound(_)) => {}
+    result => panic!("Unexpected result {:?}", result.map(|agent| agent.name)),
+  }
+  assert_eq!(requests.lock().unwrap().len(), 3);
+
+  let (client, requests) = recording_client(3);
+  match get_agent(&client, "foo").await {
+    Err(Error::NetworkError(_, _)) => {}
+    result => panic!("Unexpected result {:?}", result.map(|agent| agent.name)),
+  }
+  assert_eq!(requests.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn client_calls_can_be_spawned() {
+  let client = Arc::new(MockTransport::new().client().unwrap());
+  let handles: Vec<_> = (0..4)
+    .map(|_| {
+      let client = client.clone();
+      tokio::spawn(async move {
+        get_agent(client.as_ref(), "foo")
+          .await
+          .map(|agent| agent.name)
+      })
+    })
+    .collect();
+  for handle in handles {
+    match handle.await.unwrap() {
+      Err(Error::NotFound(_)) => {}
+      result => panic!("Unexpected result {:?}", result),
+    }
+  }
+}